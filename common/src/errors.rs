@@ -6,6 +6,12 @@ pub enum HookError {
     NoMember,
     ProposalFinalized,
     CooldownOver,
+    /// The proposal is not approved, or it can't be executed yet (eg: cooldown is not over).
+    NotExecutable,
+    /// The proposal kind is not supported by the hook.
+    BadProposalKind,
+    /// The handover from the predecessor house was already received.
+    AlreadyHandedOver,
 }
 
 impl FunctionError for HookError {
@@ -15,6 +21,9 @@ impl FunctionError for HookError {
             HookError::NoMember => panic_str("member not found"),
             HookError::ProposalFinalized => panic_str("proposal finalized"),
             HookError::CooldownOver => panic_str("cooldown period is over"),
+            HookError::NotExecutable => panic_str("proposal can't be executed"),
+            HookError::BadProposalKind => panic_str("proposal kind not supported by the hook"),
+            HookError::AlreadyHandedOver => panic_str("handover already received"),
         }
     }
 }
@@ -7,9 +7,23 @@ pub const FAILURE_CALLBACK_GAS: Gas = Gas(3 * Gas::ONE_TERA.0);
 pub const EXECUTE_CALLBACK_GAS: Gas = Gas(4 * Gas::ONE_TERA.0);
 
 pub const EXEC_CTR_CALL_GAS: Gas = Gas(8 * Gas::ONE_TERA.0);
+pub const FT_TRANSFER_GAS: Gas = Gas(10 * Gas::ONE_TERA.0);
+pub const FT_BALANCE_GAS: Gas = Gas(5 * Gas::ONE_TERA.0);
+/// Gas for the token sweep callback, which schedules the `ft_transfer`.
+pub const FT_SWEEP_CALLBACK_GAS: Gas = Gas(FT_TRANSFER_GAS.0 + 5 * Gas::ONE_TERA.0);
 pub const EXEC_SELF_GAS: Gas = Gas(20 * Gas::ONE_TERA.0);
 pub const MAX_EXEC_FUN_CALL_GAS: Gas =
     Gas(300 * Gas::ONE_TERA.0 - EXEC_SELF_GAS.0 - EXECUTE_CALLBACK_GAS.0);
 
+/// Max amount of proposals settled in a single call when the house is dissolved.
+pub const SETTLE_BATCH: u32 = 50;
+
+/// Duration of a month (30 days) in milliseconds, used for the stipend calculation.
+pub const MONTH_MS: u64 = 30 * 24 * 3600 * 1000;
+
+/// Hook methods, which take action against a member or a member proposal. Members are not
+/// allowed to vote on function call proposals calling these hooks against them.
+pub const MEMBER_HOOK_METHODS: [&str; 3] = ["dismiss_hook", "veto_hook", "reinstate_hook"];
+
 // 64bytes(accountID) + 1byte (prefix) + 4bytes(proposal_id) + vote(byte) = 72B -> add 20% margin = < 90B
 pub const VOTE_STORAGE: u64 = 90;
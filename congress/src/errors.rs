@@ -1,6 +1,8 @@
 use near_sdk::env::panic_str;
+#[cfg(any(test, not(target_arch = "wasm32")))]
+use near_sdk::serde::Deserialize;
 use near_sdk::serde::Serialize;
-use near_sdk::FunctionError;
+use near_sdk::{AccountId, FunctionError};
 
 #[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Debug))]
 pub enum VoteError {
@@ -9,6 +11,7 @@ pub enum VoteError {
     NotInProgress,
     NotActive,
     NoSelfVote,
+    Recused,
 }
 
 impl FunctionError for VoteError {
@@ -19,6 +22,7 @@ impl FunctionError for VoteError {
             VoteError::NotInProgress => panic_str("proposal not in progress"),
             VoteError::NotActive => panic_str("voting time is over"),
             VoteError::NoSelfVote => panic_str("not allowed to vote on proposal against them"),
+            VoteError::Recused => panic_str("user recused from voting on this proposal"),
         }
     }
 }
@@ -26,11 +30,19 @@ impl FunctionError for VoteError {
 #[derive(Serialize)]
 #[serde(crate = "near_sdk::serde")]
 #[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Debug))]
+#[cfg_attr(any(test, not(target_arch = "wasm32")), derive(Deserialize))]
 pub enum ExecError {
     ExecTime,
     NotApproved,
     AlreadyExecuted,
     MinVoteDuration,
+    CoApprovalPending,
+    /// The proposal depends on the proposal (ID) which is not executed yet.
+    DependencyNotExecuted(u32),
+    /// The proposal can't be executed before the given time (unix time in milliseconds).
+    NotBefore(u64),
+    /// Budget proposal can only be executed by the ratification (`ratify_budget_hook`).
+    NotRatified,
 }
 
 impl FunctionError for ExecError {
@@ -42,6 +54,14 @@ impl FunctionError for ExecError {
             }
             ExecError::AlreadyExecuted => panic_str("proposal was already executed"),
             ExecError::MinVoteDuration => panic_str("proposal still in min voting duration"),
+            ExecError::CoApprovalPending => {
+                panic_str("proposal is waiting for co-approval from other houses")
+            }
+            ExecError::DependencyNotExecuted(id) => {
+                panic_str(&format!("proposal depends on not executed proposal {}", id))
+            }
+            ExecError::NotBefore(t) => panic_str(&format!("can't be executed before {}", t)),
+            ExecError::NotRatified => panic_str("budget must be ratified by the voting body"),
         }
     }
 }
@@ -53,6 +73,13 @@ pub enum CreatePropError {
     KindNotAllowed,
     Storage(String),
     Gas(String),
+    CoApproverNotAuthorized(AccountId),
+    InvalidDependency(u32),
+    NotBeforeAfterTermEnd,
+    TokenNotSupported(AccountId),
+    DescriptionTooLong(u32),
+    TooManyOpenProposals(u32),
+    StipendAlreadySet,
 }
 
 impl FunctionError for CreatePropError {
@@ -63,6 +90,45 @@ impl FunctionError for CreatePropError {
             CreatePropError::KindNotAllowed => panic_str("proposal kind not allowed"),
             CreatePropError::Storage(reason) => panic_str(reason),
             CreatePropError::Gas(reason) => panic_str(reason),
+            CreatePropError::CoApproverNotAuthorized(house) => {
+                panic_str(&format!("{} is not authorized to co-approve", house))
+            }
+            CreatePropError::InvalidDependency(id) => {
+                panic_str(&format!("dependency proposal {} doesn't exist", id))
+            }
+            CreatePropError::NotBeforeAfterTermEnd => {
+                panic_str("not_before can't be after the end of the term")
+            }
+            CreatePropError::TokenNotSupported(token) => {
+                panic_str(&format!("token {} doesn't have a budget", token))
+            }
+            CreatePropError::DescriptionTooLong(max) => {
+                panic_str(&format!("description can't be longer than {} bytes", max))
+            }
+            CreatePropError::TooManyOpenProposals(max) => panic_str(&format!(
+                "member can't have more than {} proposals in progress",
+                max
+            )),
+            CreatePropError::StipendAlreadySet => panic_str("stipend is already set"),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Debug))]
+pub enum ClaimError {
+    NotAuthorized,
+    NoStipend,
+    NothingToClaim,
+    BudgetOverflow,
+}
+
+impl FunctionError for ClaimError {
+    fn panic(&self) -> ! {
+        match self {
+            ClaimError::NotAuthorized => panic_str("not a member nor a dismissed member"),
+            ClaimError::NoStipend => panic_str("stipend is not set"),
+            ClaimError::NothingToClaim => panic_str("nothing to claim"),
+            ClaimError::BudgetOverflow => panic_str("budget cap overflow"),
         }
     }
 }
@@ -1,7 +1,7 @@
-use near_sdk::{serde::Serialize, AccountId};
+use near_sdk::{json_types::U128, serde::Serialize, AccountId, Balance};
 use serde_json::json;
 
-use crate::{proposal::PropKind, ExecError};
+use crate::{proposal::PropKind, storage::Settlement, ExecError};
 
 use common::{EventPayload, NearEvent};
 
@@ -56,9 +56,68 @@ pub(crate) fn emit_dissolve() {
     });
 }
 
+/// Emitted when all proposals are settled, before the remaining funds are sent to the
+/// successor house or the community fund.
+pub(crate) fn emit_dissolution_report(settlement: &Settlement, swept: Balance) {
+    emit_event(EventPayload {
+        event: "dissolution-report",
+        data: json!({
+            "proposals": settlement.next_prop - 1,
+            "cancelled": settlement.cancelled,
+            "refunded": settlement.refunded,
+            "recurrent_obligations": settlement.recurrent_obligations.len(),
+            "stipends_paid": settlement.stipends_paid,
+            "swept": U128(swept),
+        }),
+    });
+}
+
 pub(crate) fn emit_dismiss(member: &AccountId) {
     emit_event(EventPayload {
         event: "dismiss",
         data: json!({ "member": member }),
     });
 }
+
+pub(crate) fn emit_set_successor(successor: &AccountId) {
+    emit_event(EventPayload {
+        event: "set-successor",
+        data: json!({ "successor": successor }),
+    });
+}
+
+/// Emitted on dissolve, when the remaining funds are handed over to the successor house.
+pub(crate) fn emit_handover(successor: &AccountId, amount: Balance) {
+    emit_event(EventPayload {
+        event: "handover",
+        data: json!({ "successor": successor, "amount": U128(amount) }),
+    });
+}
+
+pub(crate) fn emit_claim_stipend(member: &AccountId, amount: Balance) {
+    emit_event(EventPayload {
+        event: "claim-stipend",
+        data: json!({ "member": member, "amount": U128(amount) }),
+    });
+}
+
+pub(crate) fn emit_recuse(prop_id: u32, member: &AccountId) {
+    emit_event(EventPayload {
+        event: "recuse",
+        data: json!({ "prop_id": prop_id, "member": member }),
+    });
+}
+
+pub(crate) fn emit_ratify_budget(prop_id: u32, amount: Balance) {
+    emit_event(EventPayload {
+        event: "ratify-budget",
+        data: json!({ "prop_id": prop_id, "amount": U128(amount) }),
+    });
+}
+
+pub(crate) fn emit_co_approve(prop_id: u32, house: &AccountId) {
+    emit_event(EventPayload {
+        event: "co-approve",
+        data: json!({ "prop_id": prop_id, "house": house }),
+    });
+}
@@ -1,8 +1,25 @@
-use near_sdk::ext_contract;
+use common::errors::HookError;
 use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, AccountId};
+
+use crate::storage::HandoverSummary;
 
 #[ext_contract(ext_self)]
 pub trait ExtSelf {
     fn on_execute(&mut self, prop_id: u32, budget: U128);
     fn on_ban_dismiss(&mut self, prop_id: u32);
+    fn on_handover(&mut self, amount: U128);
+    fn on_ft_execute(&mut self, prop_id: u32, token: AccountId, amount: U128);
+    fn on_ft_sweep(&mut self, token: AccountId);
+}
+
+#[ext_contract(ext_ft)]
+pub trait ExtFt {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+    fn ft_balance_of(&self, account_id: AccountId) -> U128;
+}
+
+#[ext_contract(ext_congress)]
+pub trait ExtCongress {
+    fn handover_hook(&mut self, summary: HandoverSummary) -> Result<(), HookError>;
 }
@@ -1,12 +1,15 @@
-use std::cmp::min;
+// `new` has many optional configuration arguments, also copied to the generated `ContractExt`.
+#![allow(clippy::too_many_arguments)]
+
+use std::cmp::{max, min};
 use std::collections::HashMap;
 
 use common::errors::HookError;
 use common::finalize_storage_check;
 use events::*;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap};
-use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap};
+use near_sdk::json_types::U128;
 use near_sdk::{
     env, near_bindgen, require, AccountId, Balance, Gas, PanicOnDefault, Promise, PromiseError,
     PromiseOrValue, PromiseResult,
@@ -63,12 +66,47 @@ pub struct Contract {
     pub budget_cap: Balance,
     /// size (in yocto NEAR) of the big funding request
     pub big_funding_threshold: Balance,
+
+    /// House elected for the next term. When set, the remaining funds and the state summary
+    /// are handed over to the successor on dissolve, instead of being sent to the community fund.
+    pub successor: Option<AccountId>,
+    /// State summary received from the predecessor house.
+    pub handover: LazyOption<HandoverSummary>,
+
+    /// Budgets of NEP-141 tokens, keyed by the token contract.
+    pub token_budgets: LazyOption<HashMap<AccountId, TokenBudget>>,
+
+    pub proposal_limits: ProposalLimits,
+    /// Proposals created by a member, which are potentially in progress. Used to enforce
+    /// `proposal_limits.max_open_proposals`.
+    pub member_proposals: LookupMap<AccountId, Vec<u32>>,
+    /// If true, members can change their vote while the proposal is in progress.
+    pub allow_vote_change: bool,
+
+    /// Monthly stipend of each member, set by the `SetStipend` proposal.
+    pub stipend: Option<Balance>,
+    /// Total stipend claimed by a member.
+    pub stipend_claimed: LookupMap<AccountId, Balance>,
+    /// Dismissed members and the dismissal time (unix time in milliseconds).
+    pub dismissed: UnorderedMap<AccountId, u64>,
+
+    /// Settlement of the proposals, started when the house is dissolved.
+    pub settlement: Option<Settlement>,
+
+    /// Proposals (next id, last id) which are not yet converted to the current layout. Set by
+    /// `migrate` and consumed by `migrate_proposals`.
+    pub migration: Option<(u32, u32)>,
 }
 
 #[near_bindgen]
 impl Contract {
     #[init]
     /// * hook_auth : map of accounts authorized to call hooks
+    /// * token_budgets : optional map of NEP-141 token contracts and their budgets. `spent` is
+    ///   ignored and set to zero.
+    /// * proposal_limits : optional limits for creating proposals (no limits by default).
+    /// * allow_vote_change : if true, members can change their vote while the proposal is in
+    ///   progress (false by default).
     pub fn new(
         community_fund: AccountId,
         start_time: u64,
@@ -82,6 +120,9 @@ impl Contract {
         budget_cap: U128,
         big_funding_threshold: U128,
         registry: AccountId,
+        token_budgets: Option<HashMap<AccountId, TokenBudget>>,
+        proposal_limits: Option<ProposalLimits>,
+        allow_vote_change: Option<bool>,
     ) -> Self {
         // we can support up to 255 with the limitation of the proposal type, but setting 100
         // here because this is more than enough for what we need to test for Congress.
@@ -89,6 +130,10 @@ impl Contract {
         near_sdk::require!(members_len <= 100, "max amount of members is 100");
         let threshold = (members_len / 2) + 1;
         members.sort();
+        let mut token_budgets = token_budgets.unwrap_or_default();
+        for b in token_budgets.values_mut() {
+            b.spent = U128(0);
+        }
         Self {
             community_fund,
             dissolved: false,
@@ -107,6 +152,17 @@ impl Contract {
             budget_cap: budget_cap.0,
             big_funding_threshold: big_funding_threshold.0,
             registry,
+            successor: None,
+            handover: LazyOption::new(StorageKey::Handover, None),
+            token_budgets: LazyOption::new(StorageKey::TokenBudgets, Some(&token_budgets)),
+            proposal_limits: proposal_limits.unwrap_or_default(),
+            member_proposals: LookupMap::new(StorageKey::MemberProposals),
+            allow_vote_change: allow_vote_change.unwrap_or(false),
+            stipend: None,
+            stipend_claimed: LookupMap::new(StorageKey::StipendClaimed),
+            dismissed: UnorderedMap::new(StorageKey::Dismissed),
+            settlement: None,
+            migration: None,
         }
     }
 
@@ -123,12 +179,21 @@ impl Contract {
     /// Caller is required to attach enough deposit to cover the proposal storage as well as all
     /// possible votes (2*self.threshold - 1).
     /// NOTE: storage is paid from the account state
+    /// * `co_approvers`: optional list of houses, which must approve the proposal (through
+    ///   `co_approve_hook`) before it can be executed. Each of them requires `HookPerm::CoApprove`.
+    /// * `depends_on`: optional list of proposals, which must be executed before this proposal
+    ///   can be executed.
+    /// * `not_before`: optional unix time (in milliseconds) before which the proposal can't be
+    ///   executed. Must not be after the end of the house term.
     #[payable]
     #[handle_result]
     pub fn create_proposal(
         &mut self,
         kind: PropKind,
         description: String,
+        co_approvers: Option<Vec<AccountId>>,
+        depends_on: Option<Vec<u32>>,
+        not_before: Option<u64>,
     ) -> Result<u32, CreatePropError> {
         self.assert_active();
         let storage_start = env::storage_usage();
@@ -140,6 +205,17 @@ impl Contract {
         if !perms.contains(&kind.required_perm()) {
             return Err(CreatePropError::KindNotAllowed);
         }
+        if let Some(max) = self.proposal_limits.max_description_len {
+            if description.len() > max as usize {
+                return Err(CreatePropError::DescriptionTooLong(max));
+            }
+        }
+        let mut open_props = self.open_proposals(&user, members.len());
+        if let Some(max) = self.proposal_limits.max_open_proposals {
+            if open_props.len() >= max as usize {
+                return Err(CreatePropError::TooManyOpenProposals(max));
+            }
+        }
 
         let now = env::block_timestamp_ms();
         let mut new_budget = 0;
@@ -150,6 +226,19 @@ impl Contract {
             PropKind::RecurrentFundingRequest(b) => {
                 new_budget = self.budget_spent + b.0 * (self.remaining_months(now) as u128);
             }
+            PropKind::SetStipend { .. } if self.stipend.is_some() => {
+                return Err(CreatePropError::StipendAlreadySet);
+            }
+            PropKind::FtFundingRequest { token, amount, .. } => {
+                match self.token_budgets.get().unwrap().get(token) {
+                    None => return Err(CreatePropError::TokenNotSupported(token.clone())),
+                    Some(b) => {
+                        if b.spent.0 + amount.0 > b.cap.0 {
+                            return Err(CreatePropError::BudgetOverflow);
+                        }
+                    }
+                }
+            }
             PropKind::FunctionCall { actions, .. } => {
                 let mut sum_gas = 0;
                 for a in actions {
@@ -171,8 +260,29 @@ impl Contract {
         if new_budget > self.budget_cap {
             return Err(CreatePropError::BudgetOverflow);
         }
+        let mut co_approvers = co_approvers.unwrap_or_default();
+        co_approvers.sort();
+        co_approvers.dedup();
+        for h in &co_approvers {
+            if self.assert_hook_perm(h, &[HookPerm::CoApprove]).is_err() {
+                return Err(CreatePropError::CoApproverNotAuthorized(h.clone()));
+            }
+        }
+        let depends_on = depends_on.unwrap_or_default();
+        for dep in &depends_on {
+            if *dep == 0 || *dep > self.prop_counter {
+                return Err(CreatePropError::InvalidDependency(*dep));
+            }
+        }
+        if let Some(t) = not_before {
+            if t > self.end_time {
+                return Err(CreatePropError::NotBeforeAfterTermEnd);
+            }
+        }
 
         self.prop_counter += 1;
+        open_props.push(self.prop_counter);
+        self.member_proposals.insert(&user, &open_props);
         emit_prop_created(self.prop_counter, &kind);
         self.proposals.insert(
             &self.prop_counter,
@@ -187,6 +297,12 @@ impl Contract {
                 votes: HashMap::new(),
                 submission_time: now,
                 approved_at: None,
+                co_approvers,
+                co_approved: Vec::new(),
+                depends_on,
+                not_before,
+                recused: Vec::new(),
+                reserved_votes: 2 * self.threshold - 1,
             },
         );
 
@@ -219,7 +335,8 @@ impl Contract {
             return Err(VoteError::NotActive);
         }
 
-        prop.add_vote(user, vote, self.threshold)?;
+        let (_, threshold) = prop.electorate(members.len(), self.threshold);
+        prop.add_vote(user, vote, threshold, self.allow_vote_change)?;
         prop.finalize_status(
             members.len(),
             self.threshold,
@@ -242,6 +359,42 @@ impl Contract {
         Ok(())
     }
 
+    /// Allows a member to recuse from voting on the proposal (eg: due to a conflict of interest).
+    /// Recused members are excluded from the proposal electorate: the threshold is
+    /// recalculated based on the remaining members.
+    #[handle_result]
+    pub fn recuse(&mut self, id: u32) -> Result<(), VoteError> {
+        self.assert_active();
+        let user = env::predecessor_account_id();
+        let (members, _) = self.members.get().unwrap();
+        if members.binary_search(&user).is_err() {
+            return Err(VoteError::NotAuthorized);
+        }
+        let mut prop = self.assert_proposal(id);
+        if !matches!(prop.status, ProposalStatus::InProgress) {
+            return Err(VoteError::NotInProgress);
+        }
+        if env::block_timestamp_ms() > prop.submission_time + self.vote_duration {
+            return Err(VoteError::NotActive);
+        }
+        if prop.votes.contains_key(&user) {
+            return Err(VoteError::DoubleVote);
+        }
+        if prop.recused.contains(&user) {
+            return Err(VoteError::Recused);
+        }
+        prop.recused.push(user.clone());
+        prop.finalize_status(
+            members.len(),
+            self.threshold,
+            self.min_vote_duration,
+            self.vote_duration,
+        );
+        self.proposals.insert(&id, &prop);
+        emit_recuse(id, &user);
+        Ok(())
+    }
+
     /// Allows anyone to execute proposal.
     /// If `contract.cooldown` is set, then a proposal can be only executed after the cooldown:
     /// (submission_time + vote_duration + cooldown).
@@ -280,6 +433,17 @@ impl Contract {
         if self.cooldown > 0 && now <= prop.approved_at.unwrap() + self.cooldown {
             return Err(ExecError::ExecTime);
         }
+        if !prop.is_co_approved() {
+            if now <= self.co_approval_deadline(&prop) {
+                return Err(ExecError::CoApprovalPending);
+            }
+            prop.status = ProposalStatus::Rejected;
+            self.proposals.insert(&id, &prop);
+            return Ok(PromiseOrValue::Value(Err(ExecRespErr::CoApprovalExpired)));
+        }
+        if let Some(err) = self.exec_blockers(&prop).into_iter().next() {
+            return Err(err);
+        }
 
         prop.status = ProposalStatus::Executed;
         let mut result = PromiseOrValue::Value(Ok(()));
@@ -301,10 +465,53 @@ impl Contract {
                 result = promise.into();
             }
             PropKind::FundingRequest(b) => budget = b.0,
+            PropKind::FtFundingRequest {
+                token,
+                receiver_id,
+                amount,
+            } => {
+                let (token, receiver_id, amount) = (token.clone(), receiver_id.clone(), *amount);
+                let mut budgets = self.token_budgets.get().unwrap();
+                let b = budgets.get_mut(&token).expect("token budget not found");
+                b.spent.0 += amount.0;
+                if b.spent.0 > b.cap.0 {
+                    prop.status = ProposalStatus::Rejected;
+                    self.proposals.insert(&id, &prop);
+                    return Ok(PromiseOrValue::Value(Err(ExecRespErr::BudgetOverflow)));
+                }
+                self.token_budgets.set(&budgets);
+                self.proposals.insert(&id, &prop);
+
+                return Ok(PromiseOrValue::Promise(
+                    ext_ft::ext(token.clone())
+                        .with_attached_deposit(1)
+                        .with_static_gas(FT_TRANSFER_GAS)
+                        .ft_transfer(
+                            receiver_id,
+                            amount,
+                            Some(format!("congress proposal {}", id)),
+                        )
+                        .then(
+                            ext_self::ext(env::current_account_id())
+                                .with_static_gas(EXECUTE_CALLBACK_GAS)
+                                .on_ft_execute(id, token, amount),
+                        ),
+                ));
+            }
             PropKind::RecurrentFundingRequest(b) => {
                 budget = b.0 * self.remaining_months(now) as u128
             }
-            PropKind::Text => (),
+            // budget proposals are blocked (see `exec_blockers`), they are executed by the
+            // ratification.
+            PropKind::Text | PropKind::Budget(_) => (),
+            PropKind::SetStipend { amount_per_month } => {
+                if self.stipend.is_some() {
+                    prop.status = ProposalStatus::Rejected;
+                    self.proposals.insert(&id, &prop);
+                    return Ok(PromiseOrValue::Value(Err(ExecRespErr::StipendAlreadySet)));
+                }
+                self.stipend = Some(amount_per_month.0);
+            }
             PropKind::DismissAndBan { member, house } => {
                 self.proposals.insert(&id, &prop);
 
@@ -314,9 +521,9 @@ impl Contract {
                         "accounts": vec![member],
                         "memo": "".to_owned()
                     })
-                        .to_string()
-                        .as_bytes()
-                        .to_vec(),
+                    .to_string()
+                    .as_bytes()
+                    .to_vec(),
                     0,
                     EXEC_CTR_CALL_GAS,
                 );
@@ -369,46 +576,93 @@ impl Contract {
     pub fn veto_hook(&mut self, id: u32) -> Result<(), HookError> {
         self.assert_active();
         let mut proposal = self.assert_proposal(id);
-        let is_big_or_recurrent = match proposal.kind {
-            PropKind::FundingRequest(b) => b.0 >= self.big_funding_threshold,
-            PropKind::RecurrentFundingRequest(_) => true,
-            _ => false,
-        };
+        self.assert_veto_perm(&env::predecessor_account_id(), &proposal)?;
+        self.veto_deadline(&mut proposal)?;
+        proposal.status = ProposalStatus::Vetoed;
+        emit_veto(id);
+        self.proposals.insert(&id, &proposal);
+        Ok(())
+    }
+
+    /// Co-approve a joint proposal. Must be called by one of the houses listed in the
+    /// proposal `co_approvers`, before the end of the proposal cooldown.
+    /// * `id`: proposal id
+    #[handle_result]
+    pub fn co_approve_hook(&mut self, id: u32) -> Result<(), HookError> {
+        self.assert_active();
         let caller = env::predecessor_account_id();
-        if is_big_or_recurrent {
-            self.assert_hook_perm(
-                &caller,
-                &[HookPerm::VetoBigOrReccurentFundingReq, HookPerm::VetoAll],
-            )?;
-        } else {
-            self.assert_hook_perm(&caller, &[HookPerm::VetoAll])?;
+        self.assert_hook_perm(&caller, &[HookPerm::CoApprove])?;
+        let mut proposal = self.assert_proposal(id);
+        if proposal.co_approvers.binary_search(&caller).is_err() {
+            return Err(HookError::NotAuthorized);
         }
-
-        match proposal.status {
-            ProposalStatus::InProgress => {
-                proposal.status = ProposalStatus::Vetoed;
-            }
-            ProposalStatus::Approved => {
-                let cooldown = min(
-                    proposal.submission_time + self.vote_duration,
-                    proposal.approved_at.unwrap(),
-                ) + self.cooldown;
-                if cooldown < env::block_timestamp_ms() {
-                    return Err(HookError::CooldownOver);
-                }
-                proposal.status = ProposalStatus::Vetoed;
-            }
-            _ => {
-                return Err(HookError::ProposalFinalized);
-            }
+        if !matches!(
+            proposal.status,
+            ProposalStatus::InProgress | ProposalStatus::Approved | ProposalStatus::Failed
+        ) {
+            return Err(HookError::ProposalFinalized);
         }
-        emit_veto(id);
+        if self.co_approval_deadline(&proposal) < env::block_timestamp_ms() {
+            return Err(HookError::CooldownOver);
+        }
+        if proposal.co_approved.contains(&caller) {
+            // We need to return OK to allow to call this function multiple times
+            return Ok(());
+        }
+        proposal.co_approved.push(caller.clone());
+        emit_co_approve(id, &caller);
         self.proposals.insert(&id, &proposal);
         Ok(())
     }
 
-    /// Dissolve and finalize the DAO. Will send the excess account funds back to the community
-    /// fund. If the term is over can be called by anyone.
+    /// Ratifies the budget proposal (`PropKind::Budget`): the proposal is executed and the
+    /// `budget_cap` is raised by the proposal amount. The proposal must be approved, the
+    /// cooldown must be over and nothing else can block the proposal execution (co-approvals,
+    /// dependencies, `not_before`).
+    /// * `id`: proposal id
+    #[handle_result]
+    pub fn ratify_budget_hook(&mut self, id: u32) -> Result<(), HookError> {
+        self.assert_active();
+        self.assert_hook_perm(&env::predecessor_account_id(), &[HookPerm::RatifyBudget])?;
+        let mut prop = self.assert_proposal(id);
+        let amount = match prop.kind {
+            PropKind::Budget(amount) => amount.0,
+            _ => return Err(HookError::BadProposalKind),
+        };
+        if matches!(prop.status, ProposalStatus::InProgress) {
+            let (members, _) = self.members.get().unwrap();
+            prop.finalize_status(
+                members.len(),
+                self.threshold,
+                self.min_vote_duration,
+                self.vote_duration,
+            );
+        }
+        match prop.status {
+            ProposalStatus::Approved => (),
+            ProposalStatus::InProgress => return Err(HookError::NotExecutable),
+            _ => return Err(HookError::ProposalFinalized),
+        }
+        if env::block_timestamp_ms() <= prop.approved_at.unwrap() + self.cooldown
+            || self
+                .exec_blockers(&prop)
+                .iter()
+                .any(|b| !matches!(b, ExecError::NotRatified))
+        {
+            return Err(HookError::NotExecutable);
+        }
+
+        self.budget_cap += amount;
+        prop.status = ProposalStatus::Executed;
+        self.proposals.insert(&id, &prop);
+        emit_ratify_budget(id, amount);
+        emit_executed(id);
+        Ok(())
+    }
+
+    /// Dissolve and finalize the DAO. Proposals are settled (see `settle`) and then the excess
+    /// account funds are sent back to the community fund (or to the successor house).
+    /// If the term is over can be called by anyone.
     #[handle_result]
     pub fn dissolve_hook(&mut self) -> Result<(), HookError> {
         // only check permission if the DAO term is not over.
@@ -430,19 +684,75 @@ impl Contract {
             return Ok(());
         }
         members.remove(idx.unwrap());
+        self.dismissed.insert(&member, &env::block_timestamp_ms());
 
         emit_dismiss(&member);
 
         // Update threshold and members_len
         self.members_len = members.len() as u8;
         self.threshold = (self.members_len / 2) + 1;
+        // members must be updated before the settlement.
+        self.members.set(&(members, perms));
 
         // If DAO doesn't have required threshold, then we dissolve.
-        if members.len() < 2 {
+        if self.members_len < 2 {
             self.dissolve_and_cleanup();
         }
+        Ok(())
+    }
+
+    /// Transfers the stipend earned by the caller (member or dismissed member) and not claimed
+    /// yet. Stipend is earned pro-rata for the time served between `start_time` and `end_time`
+    /// (or the dismissal time). Claimed stipend counts against the `budget_cap`.
+    /// Can be called until the house is dissolved. Stipends not claimed until the dissolution
+    /// are paid out in the settlement.
+    #[handle_result]
+    pub fn claim_stipend(&mut self) -> Result<U128, ClaimError> {
+        require!(!self.dissolved, "dao is dissolved");
+        let user = env::predecessor_account_id();
+        let amount = self.claimable_stipend_of(&user)?;
+        if amount == 0 {
+            return Err(ClaimError::NothingToClaim);
+        }
+        if self.budget_spent + amount > self.budget_cap {
+            return Err(ClaimError::BudgetOverflow);
+        }
+        self.transfer_stipend(user, amount);
+        Ok(U128(amount))
+    }
 
-        self.members.set(&(members, perms));
+    /// Continues the settlement of a dissolved house: settles up to `limit` proposals.
+    /// Open proposals (in progress, approved or failed) are closed with the `Cancelled`
+    /// status and the reserved, not used, vote storage is refunded to proposers. Once all
+    /// proposals are settled, the dissolution report is emitted and the remaining funds are
+    /// swept (only once). Can be called by anyone. Returns true if the settlement is finished.
+    pub fn settle(&mut self, limit: u32) -> bool {
+        require!(self.dissolved, "dao is not dissolved");
+        self.settle_batch(limit)
+    }
+
+    /// Sets the house elected for the next term. On dissolve, the remaining funds and the
+    /// state summary will be handed over to the successor.
+    #[handle_result]
+    pub fn set_successor_hook(&mut self, successor: AccountId) -> Result<(), HookError> {
+        self.assert_active();
+        self.assert_hook_perm(&env::predecessor_account_id(), &[HookPerm::SetSuccessor])?;
+        emit_set_successor(&successor);
+        self.successor = Some(successor);
+        Ok(())
+    }
+
+    /// Receives the state summary and the remaining funds from the predecessor house.
+    /// The handover can be received only once.
+    #[payable]
+    #[handle_result]
+    pub fn handover_hook(&mut self, summary: HandoverSummary) -> Result<(), HookError> {
+        self.assert_active();
+        self.assert_hook_perm(&env::predecessor_account_id(), &[HookPerm::Handover])?;
+        if self.handover.is_some() {
+            return Err(HookError::AlreadyHandedOver);
+        }
+        self.handover.set(&summary);
         Ok(())
     }
 
@@ -471,6 +781,150 @@ impl Contract {
         Err(HookError::NotAuthorized)
     }
 
+    /// Checks if the `hook` is authorized to veto the proposal: `VetoAll` allows to veto any
+    /// proposal, `VetoBigOrReccurentFundingReq` only big or recurrent funding requests.
+    fn assert_veto_perm(&self, hook: &AccountId, prop: &Proposal) -> Result<(), HookError> {
+        let is_big_or_recurrent = match &prop.kind {
+            PropKind::FundingRequest(b) => b.0 >= self.big_funding_threshold,
+            PropKind::RecurrentFundingRequest(_) => true,
+            PropKind::FtFundingRequest { token, amount, .. } => self
+                .token_budgets
+                .get()
+                .unwrap()
+                .get(token)
+                .map_or(true, |b| amount.0 >= b.big_funding_threshold.0),
+            _ => false,
+        };
+        if is_big_or_recurrent {
+            self.assert_hook_perm(
+                hook,
+                &[HookPerm::VetoBigOrReccurentFundingReq, HookPerm::VetoAll],
+            )
+        } else {
+            self.assert_hook_perm(hook, &[HookPerm::VetoAll])
+        }
+    }
+
+    /// Finalizes the proposal status and returns the time (unix time in milliseconds) until
+    /// which the proposal can be vetoed: end of the cooldown, which starts at the approval or at
+    /// the end of the voting period. Used by both `veto_hook` and `vetoable_proposals`.
+    /// Returns error if the proposal is finalized or the cooldown is over.
+    fn veto_deadline(&self, prop: &mut Proposal) -> Result<u64, HookError> {
+        let (members, _) = self.members.get().unwrap();
+        prop.finalize_status(
+            members.len(),
+            self.threshold,
+            self.min_vote_duration,
+            self.vote_duration,
+        );
+        match prop.status {
+            ProposalStatus::InProgress | ProposalStatus::Approved => {
+                let voting_end = prop.submission_time + self.vote_duration;
+                let cooldown_start = prop.approved_at.map_or(voting_end, |t| min(voting_end, t));
+                let deadline = cooldown_start + self.cooldown;
+                if deadline < env::block_timestamp_ms() {
+                    return Err(HookError::CooldownOver);
+                }
+                Ok(deadline)
+            }
+            _ => Err(HookError::ProposalFinalized),
+        }
+    }
+
+    /// Returns IDs of the member proposals, which are in progress.
+    fn open_proposals(&self, member: &AccountId, members_num: usize) -> Vec<u32> {
+        let mut ids = self.member_proposals.get(member).unwrap_or_default();
+        ids.retain(|id| {
+            self.proposals.get(id).map_or(false, |mut p| {
+                p.finalize_status(
+                    members_num,
+                    self.threshold,
+                    self.min_vote_duration,
+                    self.vote_duration,
+                );
+                matches!(p.status, ProposalStatus::InProgress)
+            })
+        });
+        ids
+    }
+
+    fn transfer_stipend(&mut self, user: AccountId, amount: Balance) {
+        self.budget_spent += amount;
+        let claimed = self.stipend_claimed.get(&user).unwrap_or(0);
+        self.stipend_claimed.insert(&user, &(claimed + amount));
+        emit_claim_stipend(&user, amount);
+        Promise::new(user).transfer(amount);
+    }
+
+    /// Pays out the stipends earned by the members and dismissed members and not claimed yet,
+    /// up to the `budget_cap`. Returns the total amount paid.
+    fn pay_stipends(&mut self) -> Balance {
+        if self.stipend.is_none() {
+            return 0;
+        }
+        let (members, _) = self.members.get().unwrap();
+        let users: Vec<AccountId> = members.into_iter().chain(self.dismissed.keys()).collect();
+        let mut paid = 0;
+        for user in users {
+            let amount = min(
+                self.claimable_stipend_of(&user).unwrap_or(0),
+                self.budget_cap.saturating_sub(self.budget_spent),
+            );
+            if amount > 0 {
+                self.transfer_stipend(user, amount);
+                paid += amount;
+            }
+        }
+        paid
+    }
+
+    /// Returns the stipend earned by the member (or dismissed member), which was not claimed yet.
+    fn claimable_stipend_of(&self, member: &AccountId) -> Result<Balance, ClaimError> {
+        let stipend = self.stipend.ok_or(ClaimError::NoStipend)?;
+        let mut served_until = min(env::block_timestamp_ms(), self.end_time);
+        if let Some(dismissed_at) = self.dismissed.get(member) {
+            served_until = min(served_until, dismissed_at);
+        } else if !self.members.get().unwrap().0.contains(member) {
+            return Err(ClaimError::NotAuthorized);
+        }
+        let served = served_until.saturating_sub(self.start_time);
+        let earned = stipend * served as u128 / MONTH_MS as u128;
+        Ok(earned.saturating_sub(self.stipend_claimed.get(member).unwrap_or(0)))
+    }
+
+    /// Co-approvers must approve a joint proposal before the end of the longest possible
+    /// proposal cooldown.
+    fn co_approval_deadline(&self, prop: &Proposal) -> u64 {
+        prop.submission_time + self.vote_duration + self.cooldown
+    }
+
+    /// Returns reasons why an approved proposal can't be executed yet: pending co-approvals,
+    /// not executed dependencies and the execution schedule.
+    fn exec_blockers(&self, prop: &Proposal) -> Vec<ExecError> {
+        let mut blockers = Vec::new();
+        if !prop.is_co_approved() {
+            blockers.push(ExecError::CoApprovalPending);
+        }
+        for dep in &prop.depends_on {
+            let executed = self
+                .proposals
+                .get(dep)
+                .map_or(false, |p| matches!(p.status, ProposalStatus::Executed));
+            if !executed {
+                blockers.push(ExecError::DependencyNotExecuted(*dep));
+            }
+        }
+        if let Some(t) = prop.not_before {
+            if env::block_timestamp_ms() < t {
+                blockers.push(ExecError::NotBefore(t));
+            }
+        }
+        if matches!(prop.kind, PropKind::Budget(_)) {
+            blockers.push(ExecError::NotRatified);
+        }
+        blockers
+    }
+
     fn assert_proposal(&self, id: u32) -> Proposal {
         self.proposals.get(&id).expect("proposal does not exist")
     }
@@ -481,20 +935,33 @@ impl Contract {
         user: &AccountId,
     ) -> Result<(), VoteError> {
         match &prop.kind {
-            PropKind::DismissAndBan { member, house: _ } => {
-                if member == user {
-                    return Err(VoteError::NoSelfVote);
-                }
+            PropKind::DismissAndBan { member, house: _ } if member == user => {
+                return Err(VoteError::NoSelfVote);
             }
             PropKind::FunctionCall {
-                receiver_id: _,
+                receiver_id,
                 actions,
             } => {
                 for action in actions {
-                    if &action.method_name == "dismiss_hook" {
-                        let encoded =
-                            Base64VecU8(json!({ "member": user }).to_string().as_bytes().to_vec());
-                        if encoded == action.args {
+                    if !MEMBER_HOOK_METHODS.contains(&action.method_name.as_str()) {
+                        continue;
+                    }
+                    let args = match action.args_json() {
+                        Some(args) => args,
+                        // the hook call will fail, so there is no conflict of interest.
+                        None => continue,
+                    };
+                    if json_contains_str(&args, user.as_str()) {
+                        return Err(VoteError::NoSelfVote);
+                    }
+                    // veto of the user proposal in this house
+                    if action.method_name == "veto_hook"
+                        && *receiver_id == env::current_account_id()
+                    {
+                        let target = args["id"]
+                            .as_u64()
+                            .and_then(|id| self.proposals.get(&(id as u32)));
+                        if target.map_or(false, |p| &p.proposer == user) {
                             return Err(VoteError::NoSelfVote);
                         }
                     }
@@ -514,13 +981,143 @@ impl Contract {
     }
 
     fn dissolve_and_cleanup(&mut self) {
-        self.dissolved = true;
-        emit_dissolve();
+        if !self.dissolved {
+            self.dissolved = true;
+            emit_dissolve();
+            self.settlement = Some(Settlement::new());
+        }
+        self.settle_batch(SETTLE_BATCH);
+    }
+
+    /// Settles up to `limit` proposals. Sweeps the remaining funds if all proposals are
+    /// settled. Returns true if the settlement is finished.
+    fn settle_batch(&mut self, limit: u32) -> bool {
+        let mut settlement = self.settlement.take().unwrap_or_default();
+        if settlement.swept {
+            self.settlement = Some(settlement);
+            return true;
+        }
+        let (members, _) = self.members.get().unwrap();
+        let end = min(
+            self.prop_counter,
+            settlement.next_prop.saturating_add(limit).saturating_sub(1),
+        );
+        let mut refunds: HashMap<AccountId, Balance> = HashMap::new();
+        for id in settlement.next_prop..=end {
+            let mut prop = match self.proposals.get(&id) {
+                Some(p) => p,
+                None => continue,
+            };
+            prop.finalize_status(
+                members.len(),
+                self.threshold,
+                self.min_vote_duration,
+                self.vote_duration,
+            );
+            if matches!(
+                prop.status,
+                ProposalStatus::InProgress | ProposalStatus::Approved | ProposalStatus::Failed
+            ) {
+                if let PropKind::RecurrentFundingRequest(amount) = prop.kind {
+                    if !matches!(prop.status, ProposalStatus::InProgress) {
+                        settlement.recurrent_obligations.push(RecurrentObligation {
+                            prop_id: id,
+                            proposer: prop.proposer.clone(),
+                            amount,
+                        });
+                    }
+                }
+                prop.status = ProposalStatus::Cancelled;
+                settlement.cancelled += 1;
+            }
+            let unused_votes = (prop.reserved_votes as usize).saturating_sub(prop.votes.len());
+            let refund = unused_votes as u128 * VOTE_STORAGE as u128 * env::storage_byte_cost();
+            prop.reserved_votes = prop.votes.len() as u8;
+            if refund > 0 {
+                settlement.refunded.0 += refund;
+                *refunds.entry(prop.proposer.clone()).or_default() += refund;
+            }
+            self.proposals.insert(&id, &prop);
+        }
+        for (proposer, amount) in refunds {
+            Promise::new(proposer).transfer(amount);
+        }
+        settlement.next_prop = max(settlement.next_prop, end + 1);
+        let finished = settlement.next_prop > self.prop_counter;
+        if finished {
+            if !settlement.finished {
+                settlement.finished = true;
+                settlement.stipends_paid = U128(self.pay_stipends());
+                let swept = self.remaining_funds();
+                emit_dissolution_report(&settlement, swept);
+            }
+            settlement.swept = true;
+            self.settlement = Some(settlement);
+            self.sweep();
+        } else {
+            self.settlement = Some(settlement);
+        }
+        finished
+    }
+
+    /// Balance available to sweep on dissolve.
+    fn remaining_funds(&self) -> Balance {
         // we leave 10B extra storage
         let required_deposit = (env::storage_usage() + 10) as u128 * env::storage_byte_cost();
-        let diff = env::account_balance() - required_deposit;
-        if diff > 0 {
-            Promise::new(self.community_fund.clone()).transfer(diff);
+        env::account_balance().saturating_sub(required_deposit)
+    }
+
+    /// Sends the remaining funds to the successor (together with the handover summary) or to
+    /// the community fund. NEP-141 token balances (of the tokens with a budget) are sent to the
+    /// community fund.
+    fn sweep(&mut self) {
+        let diff = self.remaining_funds();
+        match self.successor.clone() {
+            Some(successor) => {
+                let summary = self.handover_summary();
+                emit_handover(&successor, diff);
+                ext_congress::ext(successor)
+                    .with_attached_deposit(diff)
+                    .with_static_gas(EXEC_CTR_CALL_GAS)
+                    .handover_hook(summary)
+                    .then(
+                        ext_self::ext(env::current_account_id())
+                            .with_static_gas(EXECUTE_CALLBACK_GAS)
+                            .on_handover(U128(diff)),
+                    );
+            }
+            None => {
+                if diff > 0 {
+                    Promise::new(self.community_fund.clone()).transfer(diff);
+                }
+            }
+        }
+        for token in self.token_budgets.get().unwrap().into_keys() {
+            ext_ft::ext(token.clone())
+                .with_static_gas(FT_BALANCE_GAS)
+                .ft_balance_of(env::current_account_id())
+                .then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(FT_SWEEP_CALLBACK_GAS)
+                        .on_ft_sweep(token),
+                );
+        }
+    }
+
+    fn handover_summary(&self) -> HandoverSummary {
+        let (members, _) = self.members.get().unwrap();
+        let recurrent_obligations = self
+            .settlement
+            .as_ref()
+            .map(|s| s.recurrent_obligations.clone())
+            .unwrap_or_default();
+        HandoverSummary {
+            end_time: self.end_time,
+            prop_counter: self.prop_counter,
+            members,
+            budget_cap: U128(self.budget_cap),
+            budget_spent: U128(self.budget_spent),
+            recurrent_obligations,
         }
     }
 
@@ -553,6 +1150,51 @@ impl Contract {
         };
     }
 
+    #[private]
+    pub fn on_ft_execute(&mut self, prop_id: u32, token: AccountId, amount: U128) {
+        assert_eq!(
+            env::promise_results_count(),
+            1,
+            "ERR_UNEXPECTED_CALLBACK_PROMISES"
+        );
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                let mut prop = self.assert_proposal(prop_id);
+                let mut budgets = self.token_budgets.get().unwrap();
+                if let Some(b) = budgets.get_mut(&token) {
+                    b.spent.0 -= amount.0;
+                }
+                self.token_budgets.set(&budgets);
+                prop.status = ProposalStatus::Failed;
+                self.proposals.insert(&prop_id, &prop);
+                emit_executed(prop_id);
+            }
+        };
+    }
+
+    /// Transfers the whole token balance of the dissolved house to the community fund.
+    #[private]
+    pub fn on_ft_sweep(
+        &mut self,
+        #[callback_result] balance: Result<U128, PromiseError>,
+        token: AccountId,
+    ) {
+        if let Ok(balance) = balance {
+            if balance.0 > 0 {
+                ext_ft::ext(token)
+                    .with_attached_deposit(1)
+                    .with_static_gas(FT_TRANSFER_GAS)
+                    .ft_transfer(
+                        self.community_fund.clone(),
+                        balance,
+                        Some("congress dissolution".to_string()),
+                    );
+            }
+        }
+    }
+
     #[private]
     pub fn on_ban_dismiss(
         &mut self,
@@ -568,16 +1210,46 @@ impl Contract {
         }
     }
 
-    /// Every house should be able to make a fun call proposals
-    pub fn add_fun_call_perm(&mut self) {
-        require!(env::predecessor_account_id() == env::current_account_id());
-        let mut m = self.members.get().unwrap();
-        if m.1.contains(&PropPerm::FunctionCall) {
-            m.1.push(PropPerm::FunctionCall);
-            self.members.set(&m);
+    /// If the successor didn't accept the handover, the funds are sent to the community fund.
+    /// The attached deposit is refunded to this contract, so the transfer is capped by the
+    /// remaining balance. The successor is removed and the sweep is re-enabled, so a refund
+    /// received later can be sent to the community fund by calling `settle`.
+    #[private]
+    pub fn on_handover(
+        &mut self,
+        #[callback_result] handover_result: Result<(), PromiseError>,
+        amount: U128,
+    ) {
+        if handover_result.is_ok() {
+            return;
+        }
+        self.successor = None;
+        if let Some(settlement) = self.settlement.as_mut() {
+            settlement.swept = false;
+        }
+        let amount = min(amount.0, self.remaining_funds());
+        if amount > 0 {
+            Promise::new(self.community_fund.clone()).transfer(amount);
+        }
+    }
+
+    /// Every house should be able to make a fun call proposals
+    pub fn add_fun_call_perm(&mut self) {
+        require!(env::predecessor_account_id() == env::current_account_id());
+        let mut m = self.members.get().unwrap();
+        if m.1.contains(&PropPerm::FunctionCall) {
+            m.1.push(PropPerm::FunctionCall);
+            self.members.set(&m);
         }
     }
 
+    /// Updates the proposal limits. Can only be called by the contract itself (through a
+    /// `FunctionCall` proposal).
+    pub fn update_proposal_limits(&mut self, limits: ProposalLimits) {
+        require!(env::predecessor_account_id() == env::current_account_id());
+        self.proposal_limits = limits;
+    }
+
     // Manually update the threshold and members_len
     pub fn update_threshold(&mut self) {
         require!(env::predecessor_account_id() == env::current_account_id());
@@ -590,12 +1262,17 @@ impl Contract {
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod unit_tests {
     use near_sdk::{
-        test_utils::{get_logs, VMContextBuilder},
+        mock::VmAction,
+        test_utils::{get_created_receipts, get_logs, VMContextBuilder},
         testing_env, VMContext,
     };
 
-    use crate::{view::MembersOutput, *};
-    use near_sdk::json_types::{U128, U64};
+    use crate::{
+        storage::{ProposalLimits, RecurrentObligation, Settlement, TokenBudget},
+        view::{MembersOutput, VetoableProposalOutput},
+        *,
+    };
+    use near_sdk::json_types::{Base64VecU8, U128, U64};
 
     /// 1ms in nano seconds
     const MSECOND: u64 = 1_000_000;
@@ -623,10 +1300,18 @@ mod unit_tests {
         AccountId::new_unchecked("coa.near".to_string())
     }
 
+    fn usdc() -> AccountId {
+        AccountId::new_unchecked("usdc.near".to_string())
+    }
+
     fn registry() -> AccountId {
         AccountId::new_unchecked("registry.near".to_string())
     }
 
+    fn elections() -> AccountId {
+        AccountId::new_unchecked("elections.near".to_string())
+    }
+
     fn setup_ctr(attach_deposit: u128) -> (VMContext, Contract, u32) {
         let mut context = VMContextBuilder::new().build();
         let end_time = START + TERM;
@@ -655,11 +1340,22 @@ mod unit_tests {
                 PropPerm::FundingRequest,
                 PropPerm::FunctionCall,
                 PropPerm::DismissAndBan,
+                PropPerm::FtFundingRequest,
             ],
             hook_perms,
             U128(10000),
             U128(1000),
             registry(),
+            Some(HashMap::from([(
+                usdc(),
+                TokenBudget {
+                    cap: U128(5000),
+                    spent: U128(100),
+                    big_funding_threshold: U128(500),
+                },
+            )])),
+            None,
+            None,
         );
         context.block_timestamp = START * MSECOND;
         context.predecessor_account_id = acc(1);
@@ -667,7 +1363,13 @@ mod unit_tests {
         testing_env!(context.clone());
 
         let id = contract
-            .create_proposal(PropKind::Text, "Proposal unit test 1".to_string())
+            .create_proposal(
+                PropKind::Text,
+                "Proposal unit test 1".to_string(),
+                None,
+                None,
+                None,
+            )
             .unwrap();
         (context, contract, id)
     }
@@ -718,11 +1420,23 @@ mod unit_tests {
         assert_eq!(res, vec![ctr.get_proposal(id).unwrap()]);
 
         let id2 = ctr
-            .create_proposal(PropKind::Text, "Proposal unit test 2".to_string())
+            .create_proposal(
+                PropKind::Text,
+                "Proposal unit test 2".to_string(),
+                None,
+                None,
+                None,
+            )
             .unwrap();
 
         let id3 = ctr
-            .create_proposal(PropKind::Text, "Proposal unit test 3".to_string())
+            .create_proposal(
+                PropKind::Text,
+                "Proposal unit test 3".to_string(),
+                None,
+                None,
+                None,
+            )
             .unwrap();
 
         // reverse query
@@ -754,7 +1468,7 @@ mod unit_tests {
         ctx.block_timestamp = START * MSECOND;
         testing_env!(ctx.clone());
         let id = ctr
-            .create_proposal(PropKind::Text, "proposal".to_owned())
+            .create_proposal(PropKind::Text, "proposal".to_owned(), None, None, None)
             .unwrap();
         assert_eq!(ctr.vote(id, Vote::Approve), Ok(()));
         assert_eq!(ctr.vote(id, Vote::Reject), Err(VoteError::DoubleVote));
@@ -776,7 +1490,13 @@ mod unit_tests {
         ctr.cooldown = 0;
         ctr.min_vote_duration = 0;
         let id = ctr
-            .create_proposal(PropKind::Text, "Proposal unit test 2".to_string())
+            .create_proposal(
+                PropKind::Text,
+                "Proposal unit test 2".to_string(),
+                None,
+                None,
+                None,
+            )
             .unwrap();
         ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id);
         let prop = ctr.get_proposal(id).unwrap();
@@ -784,7 +1504,13 @@ mod unit_tests {
 
         // create proposal, set timestamp past voting period, status should be rejected
         let id = ctr
-            .create_proposal(PropKind::Text, "Proposal unit test query 3".to_string())
+            .create_proposal(
+                PropKind::Text,
+                "Proposal unit test query 3".to_string(),
+                None,
+                None,
+                None,
+            )
             .unwrap();
         let prop = ctr.get_proposal(id).unwrap();
         ctx.block_timestamp = (prop.proposal.submission_time + ctr.vote_duration + 1) * MSECOND;
@@ -807,26 +1533,51 @@ mod unit_tests {
         let (members, _) = ctr.members.get().unwrap();
         ctr.members.set(&(members, vec![PropPerm::FundingRequest]));
 
-        ctr.create_proposal(PropKind::FundingRequest(U128(10)), "".to_string())
-            .unwrap();
+        ctr.create_proposal(
+            PropKind::FundingRequest(U128(10)),
+            "".to_string(),
+            None,
+            None,
+            None,
+        )
+        .unwrap();
 
         // creating other proposal kinds should fail
-        assert_create_prop_not_allowed(
-            ctr.create_proposal(PropKind::RecurrentFundingRequest(U128(10)), "".to_string()),
-        );
-        assert_create_prop_not_allowed(ctr.create_proposal(PropKind::Text, "".to_string()));
+        assert_create_prop_not_allowed(ctr.create_proposal(
+            PropKind::RecurrentFundingRequest(U128(10)),
+            "".to_string(),
+            None,
+            None,
+            None,
+        ));
+        assert_create_prop_not_allowed(ctr.create_proposal(
+            PropKind::Text,
+            "".to_string(),
+            None,
+            None,
+            None,
+        ));
         assert_create_prop_not_allowed(ctr.create_proposal(
             PropKind::FunctionCall {
                 receiver_id: acc(10),
                 actions: vec![],
             },
             "".to_string(),
+            None,
+            None,
+            None,
         ));
 
         ctx.attached_deposit = 1;
         testing_env!(ctx.clone());
         assert!(matches!(
-            ctr.create_proposal(PropKind::FundingRequest(U128(1)), "".to_string()),
+            ctr.create_proposal(
+                PropKind::FundingRequest(U128(1)),
+                "".to_string(),
+                None,
+                None,
+                None
+            ),
             Err(CreatePropError::Storage(_))
         ));
 
@@ -834,7 +1585,7 @@ mod unit_tests {
         ctx.attached_deposit = 10 * MILI_NEAR;
         testing_env!(ctx.clone());
         assert_eq!(
-            ctr.create_proposal(PropKind::Text, "".to_string()),
+            ctr.create_proposal(PropKind::Text, "".to_string(), None, None, None),
             Err(CreatePropError::NotAuthorized)
         );
 
@@ -849,6 +1600,9 @@ mod unit_tests {
             ctr.create_proposal(
                 PropKind::RecurrentFundingRequest(U128((ctr.budget_cap / 2) + 1)),
                 "".to_string(),
+                None,
+                None,
+                None,
             ),
             Err(CreatePropError::BudgetOverflow)
         );
@@ -900,6 +1654,9 @@ mod unit_tests {
             .create_proposal(
                 PropKind::FundingRequest(U128(1000u128)),
                 "Funding req".to_owned(),
+                None,
+                None,
+                None,
             )
             .unwrap();
 
@@ -917,6 +1674,9 @@ mod unit_tests {
         let res = ctr.create_proposal(
             PropKind::FundingRequest(U128(10000u128)),
             "Funding req".to_owned(),
+            None,
+            None,
+            None,
         );
         match res {
             Err(CreatePropError::BudgetOverflow) => (),
@@ -932,6 +1692,9 @@ mod unit_tests {
             .create_proposal(
                 PropKind::RecurrentFundingRequest(U128(10u128)),
                 "Rec Funding req".to_owned(),
+                None,
+                None,
+                None,
             )
             .unwrap();
 
@@ -962,6 +1725,9 @@ mod unit_tests {
             .create_proposal(
                 PropKind::FundingRequest((ctr.budget_cap).into()),
                 "Funding req".to_owned(),
+                None,
+                None,
+                None,
             )
             .unwrap();
         ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id1);
@@ -974,6 +1740,9 @@ mod unit_tests {
             .create_proposal(
                 PropKind::FundingRequest(10.into()),
                 "Funding req".to_owned(),
+                None,
+                None,
+                None,
             )
             .unwrap();
         ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id2);
@@ -1000,6 +1769,208 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn joint_proposal() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        let mut hooks = ctr.hook_auth.get().unwrap();
+        hooks.insert(coa(), vec![HookPerm::VetoAll, HookPerm::CoApprove]);
+        ctr.hook_auth.set(&hooks);
+
+        assert_eq!(
+            ctr.create_proposal(
+                PropKind::Text,
+                "joint".to_string(),
+                Some(vec![voting_body()]),
+                None,
+                None,
+            ),
+            Err(CreatePropError::CoApproverNotAuthorized(voting_body()))
+        );
+        let id1 = ctr
+            .create_proposal(
+                PropKind::Text,
+                "joint 1".to_string(),
+                Some(vec![coa()]),
+                None,
+                None,
+            )
+            .unwrap();
+        let id2 = ctr
+            .create_proposal(
+                PropKind::Text,
+                "joint 2".to_string(),
+                Some(vec![coa()]),
+                None,
+                None,
+            )
+            .unwrap();
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id1);
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id2);
+
+        // cooldown is over, but the proposal is not co-approved yet
+        ctx.block_timestamp = (START + COOLDOWN + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        match ctr.execute(id1) {
+            Err(ExecError::CoApprovalPending) => (),
+            Err(err) => panic!("expected CoApprovalPending got: {:?}", err),
+            Ok(_) => panic!("expected CoApprovalPending, got: OK"),
+        }
+
+        // only listed co-approvers can co-approve
+        assert_hook_not_auth(ctr.co_approve_hook(id1));
+
+        ctx.predecessor_account_id = coa();
+        testing_env!(ctx.clone());
+        ctr.co_approve_hook(id1).unwrap();
+        let expected = r#"EVENT_JSON:{"standard":"ndc-congress","version":"1.0.0","event":"co-approve","data":{"house":"coa.near","prop_id":2}}"#;
+        assert_eq!(vec![expected], get_logs());
+        // second co-approval is a noop
+        ctr.co_approve_hook(id1).unwrap();
+        assert_eq!(
+            ctr.get_proposal(id1).unwrap().proposal.co_approved,
+            vec![coa()]
+        );
+
+        assert_exec_ok(ctr.execute(id1));
+
+        // co-approval window is over
+        ctx.block_timestamp = (START + VOTE_DURATION + COOLDOWN + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.co_approve_hook(id2), Err(HookError::CooldownOver));
+        match ctr.execute(id2) {
+            Ok(PromiseOrValue::Value(resp)) => {
+                assert_eq!(resp, Err(ExecRespErr::CoApprovalExpired))
+            }
+            _ => panic!("expecting Ok ExecRespErr::CoApprovalExpired"),
+        }
+        let p2 = ctr.get_proposal(id2).unwrap();
+        assert_eq!(p2.proposal.status, ProposalStatus::Rejected);
+        assert_eq!(ctr.co_approve_hook(id2), Err(HookError::ProposalFinalized));
+    }
+
+    #[test]
+    fn proposal_dependencies_and_schedule() {
+        let (mut ctx, mut ctr, id1) = setup_ctr(100);
+        assert_eq!(
+            ctr.create_proposal(PropKind::Text, "".to_string(), None, Some(vec![5]), None),
+            Err(CreatePropError::InvalidDependency(5))
+        );
+        assert_eq!(
+            ctr.create_proposal(
+                PropKind::Text,
+                "".to_string(),
+                None,
+                None,
+                Some(START + TERM + 1)
+            ),
+            Err(CreatePropError::NotBeforeAfterTermEnd)
+        );
+
+        let not_before = START + VOTE_DURATION + COOLDOWN + 100;
+        let id2 = ctr
+            .create_proposal(
+                PropKind::Text,
+                "depends on 1".to_string(),
+                None,
+                Some(vec![id1]),
+                Some(not_before),
+            )
+            .unwrap();
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id1);
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id2);
+
+        ctx.block_timestamp = (START + COOLDOWN + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        let p2 = ctr.get_proposal(id2).unwrap();
+        assert_eq!(
+            p2.blocked,
+            vec![
+                ExecError::DependencyNotExecuted(id1),
+                ExecError::NotBefore(not_before)
+            ]
+        );
+        assert_eq!(
+            ctr.execute(id2).err(),
+            Some(ExecError::DependencyNotExecuted(id1))
+        );
+
+        assert_exec_ok(ctr.execute(id1));
+        assert_eq!(ctr.get_proposal(id1).unwrap().blocked, vec![]);
+        assert_eq!(
+            ctr.execute(id2).err(),
+            Some(ExecError::NotBefore(not_before))
+        );
+
+        ctx.block_timestamp = not_before * MSECOND;
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.get_proposal(id2).unwrap().blocked, vec![]);
+        assert_exec_ok(ctr.execute(id2));
+    }
+
+    #[test]
+    fn proposal_execution_ft_funding_req() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        // spent is reset on init
+        let budget = ctr.config().token_budgets.remove(&usdc()).unwrap();
+        assert_eq!(budget.spent, U128(0));
+
+        let ft_req = |token: AccountId, amount: u128| PropKind::FtFundingRequest {
+            token,
+            receiver_id: acc(10),
+            amount: U128(amount),
+        };
+        assert_eq!(
+            ctr.create_proposal(ft_req(acc(20), 10), "".to_string(), None, None, None),
+            Err(CreatePropError::TokenNotSupported(acc(20)))
+        );
+        assert_eq!(
+            ctr.create_proposal(ft_req(usdc(), 5001), "".to_string(), None, None, None),
+            Err(CreatePropError::BudgetOverflow)
+        );
+        let id = ctr
+            .create_proposal(ft_req(usdc(), 400), "".to_string(), None, None, None)
+            .unwrap();
+        let id_big = ctr
+            .create_proposal(ft_req(usdc(), 500), "".to_string(), None, None, None)
+            .unwrap();
+
+        // voting body can veto only big token funding requests
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx.clone());
+        assert_hook_not_auth(ctr.veto_hook(id));
+        ctr.veto_hook(id_big).unwrap();
+
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id);
+        ctx.block_timestamp = (START + VOTE_DURATION + COOLDOWN + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        match ctr.execute(id) {
+            Ok(PromiseOrValue::Promise(_)) => (),
+            _ => panic!("expecting ft_transfer promise"),
+        }
+        let budget = &ctr.config().token_budgets[&usdc()];
+        assert_eq!(budget.spent, U128(400));
+        assert_eq!(
+            ctr.get_proposal(id).unwrap().proposal.status,
+            ProposalStatus::Executed
+        );
+
+        // failed transfer rolls back the token budget
+        testing_env!(
+            ctx.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        ctr.on_ft_execute(id, usdc(), U128(400));
+        let budget = &ctr.config().token_budgets[&usdc()];
+        assert_eq!(budget.spent, U128(0));
+        assert_eq!(
+            ctr.get_proposal(id).unwrap().proposal.status,
+            ProposalStatus::Failed
+        );
+    }
+
     #[test]
     #[should_panic(expected = "dao term is over, call dissolve_hook!")]
     fn dao_dissolve_time() {
@@ -1035,7 +2006,13 @@ mod unit_tests {
 
         // Veto during cooldown
         let id = ctr
-            .create_proposal(PropKind::Text, "Proposal unit test 2".to_string())
+            .create_proposal(
+                PropKind::Text,
+                "Proposal unit test 2".to_string(),
+                None,
+                None,
+                None,
+            )
             .unwrap();
 
         // Set timestamp close to voting end duration
@@ -1058,7 +2035,13 @@ mod unit_tests {
         ctx.predecessor_account_id = acc(1);
         testing_env!(ctx.clone());
         let id = ctr
-            .create_proposal(PropKind::Text, "Proposal unit test 2".to_string())
+            .create_proposal(
+                PropKind::Text,
+                "Proposal unit test 2".to_string(),
+                None,
+                None,
+                None,
+            )
             .unwrap();
 
         ctx.block_timestamp = (START + MIN_VOTE_DURATION + 10) * MSECOND;
@@ -1089,7 +2072,13 @@ mod unit_tests {
 
     fn create_all_props(ctr: &mut Contract) -> (u32, u32, u32, u32, u32) {
         let prop_text = ctr
-            .create_proposal(PropKind::Text, "text proposal".to_string())
+            .create_proposal(
+                PropKind::Text,
+                "text proposal".to_string(),
+                None,
+                None,
+                None,
+            )
             .unwrap();
         let prop_fc = ctr
             .create_proposal(
@@ -1098,6 +2087,9 @@ mod unit_tests {
                     actions: vec![],
                 },
                 "function call proposal".to_string(),
+                None,
+                None,
+                None,
             )
             .unwrap();
 
@@ -1105,24 +2097,96 @@ mod unit_tests {
             .create_proposal(
                 PropKind::FundingRequest(U128(1100)),
                 "big funding request".to_string(),
+                None,
+                None,
+                None,
             )
             .unwrap();
         let prop_small = ctr
             .create_proposal(
                 PropKind::FundingRequest(U128(200)),
                 "small funding request".to_string(),
+                None,
+                None,
+                None,
             )
             .unwrap();
         let prop_rec = ctr
             .create_proposal(
                 PropKind::RecurrentFundingRequest(U128(200)),
                 "recurrent funding request".to_string(),
+                None,
+                None,
+                None,
             )
             .unwrap();
 
         (prop_text, prop_fc, prop_big, prop_small, prop_rec)
     }
 
+    #[test]
+    fn vetoable_proposals() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        let (p_text, p_fc, p_big, _, p_rec) = create_all_props(&mut ctr);
+        let ids = |props: Vec<VetoableProposalOutput>| -> Vec<u32> {
+            props.into_iter().map(|p| p.id).collect()
+        };
+
+        assert_eq!(
+            ids(ctr.vetoable_proposals(coa(), 0, 10)),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+        assert_eq!(ids(ctr.vetoable_proposals(coa(), 2, 2)), vec![p_text, p_fc]);
+        assert!(ctr.vetoable_proposals(acc(1), 0, 10).is_empty());
+        let vb_props = ctr.vetoable_proposals(voting_body(), 0, 10);
+        assert_eq!(ids(vb_props), vec![p_big, p_rec]);
+        assert_eq!(
+            ctr.vetoable_proposals(voting_body(), p_rec, 1)[0].veto_deadline,
+            START + VOTE_DURATION + COOLDOWN
+        );
+
+        // approved proposal can be vetoed until the end of the cooldown
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), p_big);
+        let props = ctr.vetoable_proposals(voting_body(), p_big, 1);
+        assert_eq!(props[0].veto_deadline, START + COOLDOWN);
+
+        ctx.block_timestamp = (START + COOLDOWN + 1) * MSECOND;
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx);
+        assert_eq!(
+            ids(ctr.vetoable_proposals(voting_body(), 0, 10)),
+            vec![p_rec]
+        );
+        ctr.veto_hook(p_rec).unwrap();
+        assert!(ctr.vetoable_proposals(voting_body(), 0, 10).is_empty());
+    }
+
+    #[test]
+    fn veto_deadline_of_not_finalized_proposal() {
+        let (mut ctx, mut ctr, id) = setup_ctr(100);
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id);
+        // approved before the min vote duration, the stored status is still in progress
+        let p = ctr.proposals.get(&id).unwrap();
+        assert_eq!(
+            (p.status, p.approved_at),
+            (ProposalStatus::InProgress, Some(START))
+        );
+
+        ctx.predecessor_account_id = coa();
+        ctx.block_timestamp = (START + COOLDOWN) * MSECOND;
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.vetoable_proposals(coa(), 0, 10)[0].veto_deadline,
+            START + COOLDOWN
+        );
+
+        // the view and the hook agree once the cooldown started at the approval is over
+        ctx.block_timestamp = (START + COOLDOWN + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert!(ctr.vetoable_proposals(coa(), 0, 10).is_empty());
+        assert_eq!(ctr.veto_hook(id), Err(HookError::CooldownOver));
+    }
+
     #[test]
     fn veto_hook_big_funding_request() {
         let (mut ctx, mut ctr, _) = setup_ctr(100);
@@ -1165,16 +2229,211 @@ mod unit_tests {
 
         ctr.dissolve_hook().unwrap();
         let expected = r#"EVENT_JSON:{"standard":"ndc-congress","version":"1.0.0","event":"dissolve","data":""}"#;
-        assert_eq!(vec![expected], get_logs());
+        let logs = get_logs();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0], expected);
+        // the setup proposal is cancelled and the storage reserved for 5 votes is refunded.
+        assert!(logs[1].contains(r#""event":"dissolution-report","data":{"cancelled":1,"proposals":1,"recurrent_obligations":0,"refunded":"4500000000000000000000""#));
         assert!(ctr.dissolved);
+        assert_eq!(
+            ctr.get_proposal(1).unwrap().proposal.status,
+            ProposalStatus::Cancelled
+        );
 
         ctr.create_proposal(
             PropKind::FundingRequest(U128(10000u128)),
             "Funding req".to_owned(),
+            None,
+            None,
+            None,
         )
         .unwrap();
     }
 
+    #[test]
+    fn dissolve_sweeps_tokens() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx.clone());
+        ctr.dissolve_hook().unwrap();
+        let calls: Vec<(AccountId, String)> = get_created_receipts()
+            .into_iter()
+            .flat_map(|r| {
+                r.actions.into_iter().filter_map(move |a| match a {
+                    VmAction::FunctionCall { function_name, .. } => {
+                        Some((r.receiver_id.clone(), function_name))
+                    }
+                    _ => None,
+                })
+            })
+            .collect();
+        assert!(calls.contains(&(usdc(), "ft_balance_of".to_string())));
+
+        // the token balance is transferred to the community fund
+        ctx.predecessor_account_id = ctx.current_account_id.clone();
+        testing_env!(ctx);
+        ctr.on_ft_sweep(Ok(U128(700)), usdc());
+        let receipts = get_created_receipts();
+        let r = receipts.last().unwrap();
+        assert_eq!(r.receiver_id, usdc());
+        match &r.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                deposit,
+                ..
+            } => {
+                assert_eq!(function_name, "ft_transfer");
+                assert_eq!(*deposit, 1);
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["receiver_id"], community_fund().to_string());
+                assert_eq!(args["amount"], "700");
+            }
+            a => panic!("expected ft_transfer, got: {:?}", a),
+        }
+    }
+
+    #[test]
+    fn successor_handover() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        let mut hooks = ctr.hook_auth.get().unwrap();
+        hooks.insert(elections(), vec![HookPerm::SetSuccessor]);
+        hooks.insert(acc(20), vec![HookPerm::Handover]);
+        ctr.hook_auth.set(&hooks);
+
+        let id = ctr
+            .create_proposal(
+                PropKind::RecurrentFundingRequest(U128(10)),
+                "rec funding req".to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id);
+        ctx.block_timestamp = (START + MIN_VOTE_DURATION + 10) * MSECOND;
+
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx.clone());
+        assert_hook_not_auth(ctr.set_successor_hook(acc(10)));
+
+        ctx.predecessor_account_id = elections();
+        testing_env!(ctx.clone());
+        ctr.set_successor_hook(acc(10)).unwrap();
+        assert_eq!(ctr.successor(), Some(acc(10)));
+
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx.clone());
+        ctr.dissolve_hook().unwrap();
+        assert!(ctr.dissolved);
+        assert_eq!(
+            get_logs().len(),
+            3,
+            "expecting dissolve, dissolution report and handover events"
+        );
+
+        let summary = ctr.handover_summary();
+        assert_eq!(
+            summary.recurrent_obligations,
+            vec![RecurrentObligation {
+                prop_id: id,
+                proposer: acc(1),
+                amount: U128(10),
+            }]
+        );
+        assert_eq!(summary.members, vec![acc(1), acc(2), acc(3), acc(4)]);
+        assert_eq!(
+            ctr.get_proposal(id).unwrap().proposal.status,
+            ProposalStatus::Cancelled
+        );
+    }
+
+    #[test]
+    fn handover_hook() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        let summary = HandoverSummary {
+            end_time: START,
+            prop_counter: 10,
+            members: vec![acc(1), acc(2)],
+            budget_cap: U128(1000),
+            budget_spent: U128(100),
+            recurrent_obligations: vec![RecurrentObligation {
+                prop_id: 2,
+                proposer: acc(1),
+                amount: U128(10),
+            }],
+        };
+        let mut hooks = ctr.hook_auth.get().unwrap();
+        hooks.insert(acc(20), vec![HookPerm::Handover]);
+        ctr.hook_auth.set(&hooks);
+        assert_hook_not_auth(ctr.handover_hook(summary.clone()));
+        ctx.predecessor_account_id = acc(20);
+        testing_env!(ctx);
+        ctr.handover_hook(summary.clone()).unwrap();
+        assert_eq!(ctr.handover(), Some(summary.clone()));
+        assert_eq!(
+            ctr.handover_hook(summary),
+            Err(HookError::AlreadyHandedOver)
+        );
+    }
+
+    #[test]
+    fn on_handover_failed() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        ctr.successor = Some(acc(10));
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx.clone());
+        ctr.dissolve_hook().unwrap();
+        assert!(ctr.settlement().unwrap().swept);
+
+        ctx.predecessor_account_id = ctx.current_account_id.clone();
+        testing_env!(ctx);
+        let available = ctr.remaining_funds();
+        ctr.on_handover(Err(PromiseError::Failed), U128(available + 1000));
+        assert_eq!(ctr.successor(), None);
+        assert!(!ctr.settlement().unwrap().swept);
+        let r = get_created_receipts().pop().unwrap();
+        assert_eq!(r.receiver_id, community_fund());
+        assert_eq!(r.actions, vec![VmAction::Transfer { deposit: available }]);
+    }
+
+    #[test]
+    fn settle_in_batches() {
+        let (mut ctx, mut ctr, id1) = setup_ctr(100);
+        let id2 = ctr
+            .create_proposal(PropKind::Text, "".to_string(), None, None, None)
+            .unwrap();
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id2);
+        ctx.block_timestamp = (START + VOTE_DURATION + COOLDOWN + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert_exec_ok(ctr.execute(id2));
+
+        // start the settlement manually to check batches
+        ctr.dissolved = true;
+        ctr.settlement = Some(Settlement::new());
+        assert!(!ctr.settle(1));
+        let s = ctr.settlement().unwrap();
+        assert_eq!((s.next_prop, s.cancelled, s.finished), (2, 0, false));
+        // the first proposal is rejected (vote duration is over)
+        let p1 = ctr.get_proposal(id1).unwrap().proposal;
+        assert_eq!(
+            (p1.status, p1.reserved_votes),
+            (ProposalStatus::Rejected, 0)
+        );
+
+        assert!(ctr.settle(1));
+        let s = ctr.settlement().unwrap();
+        assert_eq!((s.next_prop, s.cancelled, s.finished), (3, 0, true));
+        // 5 reserved votes, 3 used in the second proposal
+        let vote_storage = VOTE_STORAGE as u128 * env::storage_byte_cost();
+        assert_eq!(s.refunded, U128(7 * vote_storage));
+        let p2 = ctr.get_proposal(id2).unwrap().proposal;
+        assert_eq!(
+            (p2.status, p2.reserved_votes),
+            (ProposalStatus::Executed, 3)
+        );
+    }
+
     #[test]
     fn dismiss_hook() {
         let (mut ctx, mut ctr, id) = setup_ctr(100);
@@ -1223,7 +2482,10 @@ mod unit_tests {
 
         // Check if member was successfully removed
         let (members, _) = ctr.members.get().unwrap();
-        assert!(!members.contains(&acc(2)), "Member 2 should have been removed");
+        assert!(
+            !members.contains(&acc(2)),
+            "Member 2 should have been removed"
+        );
 
         // Check if members count and threshold are updated correctly
         assert_eq!(ctr.members_len, 3, "Members length should be updated to 3");
@@ -1237,6 +2499,41 @@ mod unit_tests {
         assert!(ctr.dissolved);
     }
 
+    #[test]
+    fn dismiss_dissolve_handover() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        ctr.successor = Some(acc(10));
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx);
+        for member in [acc(1), acc(2), acc(3)] {
+            ctr.dismiss_hook(member).unwrap();
+        }
+        assert!(ctr.dissolved);
+        // the handover summary is sent with the updated members
+        let handover = get_created_receipts()
+            .into_iter()
+            .find(|r| r.receiver_id == acc(10))
+            .unwrap();
+        match &handover.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                ..
+            } => {
+                assert_eq!(function_name, "handover_hook");
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["summary"]["members"], serde_json::json!([acc(4)]));
+            }
+            a => panic!("expected handover_hook, got: {:?}", a),
+        }
+
+        // the remaining funds are swept only once
+        let receipts = get_created_receipts().len();
+        assert!(ctr.settle(10));
+        assert!(ctr.settlement().unwrap().swept);
+        assert_eq!(get_created_receipts().len(), receipts);
+    }
+
     #[test]
     fn dismiss_order() {
         let (mut ctx, mut ctr, _) = setup_ctr(100);
@@ -1281,6 +2578,9 @@ mod unit_tests {
                     house: coa(),
                 },
                 "Motion to remove member and ban".to_string(),
+                None,
+                None,
+                None,
             )
             .unwrap();
 
@@ -1327,6 +2627,9 @@ mod unit_tests {
                     house: coa(),
                 },
                 "Motion to remove member and ban".to_string(),
+                None,
+                None,
+                None,
             )
             .unwrap();
 
@@ -1356,6 +2659,9 @@ mod unit_tests {
                     .to_vec(),
                 },
                 "Proposal to remove member".to_string(),
+                None,
+                None,
+                None,
             )
             .unwrap();
 
@@ -1364,6 +2670,351 @@ mod unit_tests {
         assert_eq!(ctr.vote(prop, Vote::Approve), Err(VoteError::NoSelfVote));
     }
 
+    #[test]
+    fn hook_call_vote_against() {
+        let (mut ctx, mut ctr, id) = setup_ctr(100);
+        let fun_call =
+            |receiver_id: AccountId, method_name: &str, args: &str| PropKind::FunctionCall {
+                receiver_id,
+                actions: vec![ActionCall {
+                    method_name: method_name.to_string(),
+                    args: Base64VecU8(args.as_bytes().to_vec()),
+                    deposit: U128(0),
+                    gas: U64(EXEC_CTR_CALL_GAS.0),
+                }],
+            };
+        // whitespace and other arguments don't change the detection
+        let dismiss = fun_call(
+            coa(),
+            "dismiss_hook",
+            r#"{ "memo": "x",  "member" : "user-2.near" }"#,
+        );
+        let p_dismiss = ctr
+            .create_proposal(dismiss, "".to_string(), None, None, None)
+            .unwrap();
+        // veto of the acc(1) proposal in this house
+        let veto = fun_call(
+            env::current_account_id(),
+            "veto_hook",
+            &format!(r#"{{"id": {}}}"#, id),
+        );
+        let p_veto = ctr
+            .create_proposal(veto, "".to_string(), None, None, None)
+            .unwrap();
+
+        ctx.predecessor_account_id = acc(2);
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.vote(p_dismiss, Vote::Approve),
+            Err(VoteError::NoSelfVote)
+        );
+        ctr.vote(p_veto, Vote::Approve).unwrap();
+
+        ctx.predecessor_account_id = acc(1);
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.vote(p_veto, Vote::Approve), Err(VoteError::NoSelfVote));
+        ctr.vote(p_dismiss, Vote::Approve).unwrap();
+    }
+
+    #[test]
+    fn proposal_limits() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        assert_eq!(ctr.config().proposal_limits, ProposalLimits::default());
+        let limits = ProposalLimits {
+            max_description_len: Some(10),
+            max_open_proposals: Some(2),
+        };
+        ctx.predecessor_account_id = env::current_account_id();
+        testing_env!(ctx.clone());
+        ctr.update_proposal_limits(limits.clone());
+        assert_eq!(ctr.config().proposal_limits, limits);
+
+        ctx.predecessor_account_id = acc(1);
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.create_proposal(PropKind::Text, "x".repeat(11), None, None, None),
+            Err(CreatePropError::DescriptionTooLong(10))
+        );
+        // acc(1) already has one proposal in progress (created in the setup)
+        let id = ctr
+            .create_proposal(PropKind::Text, "x".repeat(10), None, None, None)
+            .unwrap();
+        assert_eq!(
+            ctr.create_proposal(PropKind::Text, "".to_string(), None, None, None),
+            Err(CreatePropError::TooManyOpenProposals(2))
+        );
+
+        // other members are not affected
+        ctx.predecessor_account_id = acc(2);
+        testing_env!(ctx.clone());
+        ctr.create_proposal(PropKind::Text, "".to_string(), None, None, None)
+            .unwrap();
+
+        // finalized proposals don't count
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id);
+        ctx.block_timestamp = (START + MIN_VOTE_DURATION + 10) * MSECOND;
+        ctx.predecessor_account_id = acc(1);
+        testing_env!(ctx.clone());
+        ctr.create_proposal(PropKind::Text, "".to_string(), None, None, None)
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "assertion failed")]
+    fn update_proposal_limits_not_self() {
+        let (_, mut ctr, _) = setup_ctr(100);
+        ctr.update_proposal_limits(ProposalLimits::default());
+    }
+
+    #[test]
+    fn vote_change() {
+        let (mut ctx, mut ctr, id) = setup_ctr(100);
+        ctr.allow_vote_change = true;
+        ctr = vote(ctx.clone(), ctr, [acc(1), acc(2), acc(3)].to_vec(), id);
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!((p.approve, p.approved_at), (3, Some(START)));
+
+        // withdraw the approval
+        ctx.block_timestamp = (START + 10) * MSECOND;
+        ctx.predecessor_account_id = acc(2);
+        testing_env!(ctx.clone());
+        ctr.vote(id, Vote::Reject).unwrap();
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!((p.approve, p.reject, p.approved_at), (2, 1, None));
+        assert_eq!(p.votes[&acc(2)].vote, Vote::Reject);
+
+        ctr.vote(id, Vote::Abstain).unwrap();
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!((p.approve, p.reject, p.abstain), (2, 0, 1));
+
+        // approve again
+        ctx.block_timestamp = (START + 20) * MSECOND;
+        testing_env!(ctx.clone());
+        ctr.vote(id, Vote::Approve).unwrap();
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!(
+            (p.approve, p.abstain, p.approved_at),
+            (3, 0, Some(START + 20))
+        );
+
+        // re-casting the same vote doesn't reset the approval time
+        ctx.block_timestamp = (START + 30) * MSECOND;
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.vote(id, Vote::Approve), Err(VoteError::DoubleVote));
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!((p.approve, p.approved_at), (3, Some(START + 20)));
+
+        // can't change the vote once the proposal is finalized
+        ctx.predecessor_account_id = acc(4);
+        testing_env!(ctx.clone());
+        ctr.vote(id, Vote::Approve).unwrap();
+        assert_eq!(ctr.vote(id, Vote::Reject), Err(VoteError::NotInProgress));
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!((p.approve, p.approved_at), (4, Some(START + 20)));
+    }
+
+    #[test]
+    fn vote_change_approved_at() {
+        let (mut ctx, ctr, id) = setup_ctr(100);
+        let mut p = ctr.get_proposal(id).unwrap().proposal;
+        let mut set_time = |t: u64| {
+            ctx.block_timestamp = t * MSECOND;
+            testing_env!(ctx.clone());
+        };
+        set_time(START);
+        for a in [acc(1), acc(2), acc(3)] {
+            p.add_vote(a, Vote::Approve, 3, true).unwrap();
+        }
+        p.add_vote(acc(4), Vote::Reject, 3, true).unwrap();
+        assert_eq!((p.approve, p.approved_at), (3, Some(START)));
+
+        // changing to approve above the threshold doesn't reset the approval time
+        set_time(START + 10);
+        p.add_vote(acc(4), Vote::Approve, 3, true).unwrap();
+        assert_eq!((p.approve, p.approved_at), (4, Some(START)));
+
+        // withdrawing an approval, but staying at the threshold, keeps the approval time
+        p.add_vote(acc(4), Vote::Reject, 3, true).unwrap();
+        assert_eq!((p.approve, p.approved_at), (3, Some(START)));
+
+        // dropping below the threshold clears it, crossing it again sets a new one
+        p.add_vote(acc(1), Vote::Reject, 3, true).unwrap();
+        assert_eq!((p.approve, p.approved_at), (2, None));
+        set_time(START + 20);
+        p.add_vote(acc(1), Vote::Approve, 3, true).unwrap();
+        assert_eq!((p.approve, p.approved_at), (3, Some(START + 20)));
+    }
+
+    #[test]
+    fn stipend() {
+        let (mut ctx, mut ctr, _) = setup_ctr(100);
+        let mut perms = ctr.members.get().unwrap();
+        perms.1.push(PropPerm::SetStipend);
+        ctr.members.set(&perms);
+        ctr.end_time = START + 3 * MONTH_MS;
+        ctr.budget_cap = 1000;
+        assert_eq!(ctr.claim_stipend(), Err(ClaimError::NoStipend));
+
+        let kind = PropKind::SetStipend {
+            amount_per_month: U128(100),
+        };
+        let id = ctr
+            .create_proposal(kind, "".to_string(), None, None, None)
+            .unwrap();
+        ctr = vote(
+            ctx.clone(),
+            ctr,
+            [acc(1), acc(2), acc(3), acc(4)].to_vec(),
+            id,
+        );
+        ctx.block_timestamp = (START + COOLDOWN + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert_exec_ok(ctr.execute(id));
+        assert_eq!(ctr.config().stipend, Some(U128(100)));
+        let kind = PropKind::SetStipend {
+            amount_per_month: U128(200),
+        };
+        assert_eq!(
+            ctr.create_proposal(kind, "".to_string(), None, None, None),
+            Err(CreatePropError::StipendAlreadySet)
+        );
+
+        // half month served
+        ctx.block_timestamp = (START + MONTH_MS / 2) * MSECOND;
+        ctx.predecessor_account_id = acc(1);
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.claimable_stipend(acc(1)), U128(50));
+        assert_eq!(ctr.claim_stipend(), Ok(U128(50)));
+        assert_eq!(ctr.claim_stipend(), Err(ClaimError::NothingToClaim));
+        assert_eq!(ctr.budget_spent, 50);
+
+        ctx.predecessor_account_id = acc(10);
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.claim_stipend(), Err(ClaimError::NotAuthorized));
+
+        // dismissed member is paid until the dismissal
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx.clone());
+        ctr.dismiss_hook(acc(2)).unwrap();
+
+        // stipend is paid until the end of the term
+        ctx.block_timestamp = (START + 4 * MONTH_MS) * MSECOND;
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.claimable_stipend(acc(1)), U128(250));
+        assert_eq!(ctr.claimable_stipend(acc(2)), U128(50));
+        ctx.predecessor_account_id = acc(2);
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.claim_stipend(), Ok(U128(50)));
+
+        // budget cap
+        ctr.budget_cap = 300;
+        ctx.predecessor_account_id = acc(3);
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.claim_stipend(), Err(ClaimError::BudgetOverflow));
+
+        // unclaimed stipends are paid out on dissolve, up to the budget cap
+        ctr.budget_cap = 800;
+        ctr.dissolve_hook().unwrap();
+        assert_eq!(ctr.settlement().unwrap().stipends_paid, U128(700));
+        assert_eq!(ctr.budget_spent, 800);
+        assert_eq!(ctr.claimable_stipend(acc(1)), U128(0));
+        assert_eq!(ctr.claimable_stipend(acc(3)), U128(0));
+        assert_eq!(ctr.claimable_stipend(acc(4)), U128(150));
+    }
+
+    #[test]
+    fn ratify_budget() {
+        let (mut ctx, mut ctr, text_id) = setup_ctr(100);
+        let mut perms = ctr.members.get().unwrap();
+        perms.1.push(PropPerm::Budget);
+        ctr.members.set(&perms);
+        let id = ctr
+            .create_proposal(
+                PropKind::Budget(U128(1000)),
+                "".to_string(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx.clone());
+        assert_hook_not_auth(ctr.ratify_budget_hook(id));
+        let mut hooks = ctr.hook_auth.get().unwrap();
+        hooks.insert(voting_body(), vec![HookPerm::RatifyBudget]);
+        ctr.hook_auth.set(&hooks);
+        assert_eq!(
+            ctr.ratify_budget_hook(text_id),
+            Err(HookError::BadProposalKind)
+        );
+        assert_eq!(ctr.ratify_budget_hook(id), Err(HookError::NotExecutable));
+
+        ctr = vote(
+            ctx.clone(),
+            ctr,
+            [acc(1), acc(2), acc(3), acc(4)].to_vec(),
+            id,
+        );
+        // cooldown is not over
+        ctx.predecessor_account_id = voting_body();
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.ratify_budget_hook(id), Err(HookError::NotExecutable));
+
+        // budget can't be executed without the ratification
+        ctx.block_timestamp = (START + COOLDOWN + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        match ctr.execute(id) {
+            Ok(_) => panic!("expected NotRatified, got: OK"),
+            Err(err) => assert_eq!(err, ExecError::NotRatified),
+        }
+        assert_eq!(
+            ctr.get_proposal(id).unwrap().blocked,
+            vec![ExecError::NotRatified]
+        );
+
+        assert_eq!(ctr.ratify_budget_hook(id), Ok(()));
+        assert_eq!(ctr.budget_cap, 11000);
+        let p = ctr.get_proposal(id).unwrap();
+        assert_eq!(p.proposal.status, ProposalStatus::Executed);
+        assert_eq!(
+            ctr.ratify_budget_hook(id),
+            Err(HookError::ProposalFinalized)
+        );
+    }
+
+    #[test]
+    fn recuse() {
+        let (mut ctx, mut ctr, id) = setup_ctr(100);
+        ctr.vote(id, Vote::Approve).unwrap();
+        assert_eq!(ctr.recuse(id), Err(VoteError::DoubleVote));
+
+        ctx.predecessor_account_id = acc(5);
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.recuse(id), Err(VoteError::NotAuthorized));
+
+        // 4 members, threshold = 3. With 2 recused members, 2 remaining members and threshold = 2.
+        ctx.predecessor_account_id = acc(2);
+        testing_env!(ctx.clone());
+        ctr.recuse(id).unwrap();
+        let expected = r#"EVENT_JSON:{"standard":"ndc-congress","version":"1.0.0","event":"recuse","data":{"member":"user-2.near","prop_id":1}}"#;
+        assert_eq!(vec![expected], get_logs());
+        assert_eq!(ctr.recuse(id), Err(VoteError::Recused));
+        assert_eq!(ctr.vote(id, Vote::Approve), Err(VoteError::Recused));
+
+        ctx.predecessor_account_id = acc(3);
+        testing_env!(ctx.clone());
+        ctr.recuse(id).unwrap();
+
+        ctx.predecessor_account_id = acc(4);
+        testing_env!(ctx.clone());
+        ctr.vote(id, Vote::Approve).unwrap();
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!(p.recused, vec![acc(2), acc(3)]);
+        assert_eq!(p.status, ProposalStatus::Approved);
+        assert_eq!(p.approved_at, Some(START));
+    }
+
     #[test]
     fn abstain_vote() {
         let (_, mut ctr, id) = setup_ctr(100);
@@ -1460,4 +3111,83 @@ mod unit_tests {
         let (_, ctr, _) = setup_ctr(100);
         assert_eq!(ctr.members_len(), 4);
     }
+
+    #[test]
+    fn migrate_proposals() {
+        let mut ctx = VMContextBuilder::new().build();
+        ctx.predecessor_account_id = ctx.current_account_id.clone();
+        testing_env!(ctx);
+        let old_prop = migrate::OldProposal {
+            proposer: acc(1),
+            description: "old".to_string(),
+            kind: PropKind::Text,
+            status: ProposalStatus::Approved,
+            approve: 1,
+            reject: 0,
+            abstain: 0,
+            votes: HashMap::from([(
+                acc(1),
+                VoteRecord {
+                    timestamp: 10,
+                    vote: Vote::Approve,
+                },
+            )]),
+            submission_time: 5,
+            approved_at: Some(10),
+        };
+        // many proposals are migrated in batches, removed proposals are skipped
+        let mut proposals = LookupMap::new(StorageKey::Proposals);
+        for id in (1..=25).filter(|id| *id != 7) {
+            proposals.insert(&id, &old_prop);
+        }
+        let old = migrate::OldState {
+            community_fund: community_fund(),
+            registry: registry(),
+            dissolved: false,
+            prop_counter: 25,
+            proposals,
+            members: LazyOption::new(
+                StorageKey::Members,
+                Some(&(vec![acc(1), acc(2)], vec![PropPerm::Text])),
+            ),
+            members_len: 2,
+            threshold: 2,
+            hook_auth: LazyOption::new(StorageKey::HookAuth, Some(&HashMap::new())),
+            start_time: START,
+            end_time: START + TERM,
+            cooldown: COOLDOWN,
+            vote_duration: VOTE_DURATION,
+            min_vote_duration: MIN_VOTE_DURATION,
+            budget_spent: 0,
+            budget_cap: 1000,
+            big_funding_threshold: 100,
+        };
+        env::state_write(&old);
+
+        let mut ctr = Contract::migrate();
+        assert_eq!(ctr.migration, Some((1, 25)));
+        assert!(!ctr.migrate_proposals(10));
+        assert_eq!(ctr.migration, Some((11, 25)));
+        assert!(ctr.get_proposal(10).is_some());
+        assert!(!ctr.migrate_proposals(10));
+        assert!(ctr.migrate_proposals(10));
+        assert_eq!(ctr.migration, None);
+        assert!(ctr.migrate_proposals(10));
+        for id in (1..=25).filter(|id| *id != 7) {
+            assert_eq!(ctr.get_proposal(id).unwrap().proposal.description, "old");
+        }
+        assert!(ctr.get_proposal(7).is_none());
+
+        let p = ctr.get_proposal(1).unwrap().proposal;
+        assert_eq!(p.description, "old");
+        assert_eq!(p.status, ProposalStatus::Approved);
+        assert_eq!(p.approved_at, Some(10));
+        assert_eq!(p.votes.len(), 1);
+        assert!(p.co_approvers.is_empty());
+        assert!(p.depends_on.is_empty());
+        assert_eq!(p.not_before, None);
+        assert!(p.recused.is_empty());
+        assert_eq!(p.reserved_votes, 0);
+        assert!(ctr.get_proposal(26).is_none());
+    }
 }
@@ -1,19 +1,60 @@
 use crate::*;
+
+/// Proposal before adding co-approvals, dependencies, recusals and reserved votes.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldProposal {
+    pub proposer: AccountId,
+    pub description: String,
+    pub kind: PropKind,
+    pub status: ProposalStatus,
+    pub approve: u8,
+    pub reject: u8,
+    pub abstain: u8,
+    pub votes: HashMap<AccountId, VoteRecord>,
+    pub submission_time: u64,
+    pub approved_at: Option<u64>,
+}
+
+impl From<OldProposal> for Proposal {
+    fn from(p: OldProposal) -> Self {
+        Proposal {
+            proposer: p.proposer,
+            description: p.description,
+            kind: p.kind,
+            status: p.status,
+            approve: p.approve,
+            reject: p.reject,
+            abstain: p.abstain,
+            votes: p.votes,
+            submission_time: p.submission_time,
+            approved_at: p.approved_at,
+            co_approvers: Vec::new(),
+            co_approved: Vec::new(),
+            depends_on: Vec::new(),
+            not_before: None,
+            recused: Vec::new(),
+            // storage of old proposals was not prepaid.
+            reserved_votes: 0,
+        }
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct OldState {
     pub community_fund: AccountId,
     pub registry: AccountId,
     pub dissolved: bool,
     pub prop_counter: u32,
-    pub proposals: LookupMap<u32, Proposal>,
+    pub proposals: LookupMap<u32, OldProposal>,
     pub members: LazyOption<(Vec<AccountId>, Vec<PropPerm>)>,
+    pub members_len: u8,
     pub threshold: u8,
     pub hook_auth: LazyOption<HashMap<AccountId, Vec<HookPerm>>>,
     pub start_time: u64,
     pub end_time: u64,
     pub cooldown: u64,
-    pub voting_duration: u64,
-    pub min_voting_duration: u64,
+    pub vote_duration: u64,
+    pub min_vote_duration: u64,
     pub budget_spent: Balance,
     pub budget_cap: Balance,
     pub big_funding_threshold: Balance,
@@ -21,35 +62,83 @@ pub struct OldState {
 
 #[near_bindgen]
 impl Contract {
+    /// Migrates the contract state. Stored proposals are converted in batches with
+    /// `migrate_proposals`. Until then, the old proposals can't be read.
     #[private]
     #[init(ignore_state)]
-    /* pub  */
     pub fn migrate() -> Self {
         let old_state: OldState = env::state_read().expect("failed");
-        // new field in the smart contract :
-        // + members_len: u8,
-        // new field field in proposal
-
-        let members_len = old_state.members.get().unwrap().0.len() as u8;
+        // new fields in the smart contract :
+        // + successor: Option<AccountId>,
+        // + handover: LazyOption<HandoverSummary>,
+        // + token_budgets: LazyOption<HashMap<AccountId, TokenBudget>>,
+        // + proposal_limits: ProposalLimits,
+        // + member_proposals: LookupMap<AccountId, Vec<u32>>,
+        // + allow_vote_change: bool,
+        // + stipend: Option<Balance>,
+        // + stipend_claimed: LookupMap<AccountId, Balance>,
+        // + dismissed: UnorderedMap<AccountId, u64>,
+        // + settlement: Option<Settlement>,
+        // + migration: Option<(u32, u32)>,
 
         Self {
             community_fund: old_state.community_fund,
             registry: old_state.registry,
             dissolved: old_state.dissolved,
             prop_counter: old_state.prop_counter,
-            proposals: old_state.proposals,
+            proposals: LookupMap::new(StorageKey::Proposals),
             members: old_state.members,
-            members_len,
+            members_len: old_state.members_len,
             threshold: old_state.threshold,
             hook_auth: old_state.hook_auth,
             start_time: old_state.start_time,
             end_time: old_state.end_time,
             cooldown: old_state.cooldown,
-            vote_duration: old_state.voting_duration,
-            min_vote_duration: old_state.min_voting_duration,
+            vote_duration: old_state.vote_duration,
+            min_vote_duration: old_state.min_vote_duration,
             budget_spent: old_state.budget_spent,
             budget_cap: old_state.budget_cap,
             big_funding_threshold: old_state.big_funding_threshold,
+            successor: None,
+            handover: LazyOption::new(StorageKey::Handover, None),
+            token_budgets: LazyOption::new(StorageKey::TokenBudgets, Some(&HashMap::new())),
+            proposal_limits: ProposalLimits::default(),
+            member_proposals: LookupMap::new(StorageKey::MemberProposals),
+            allow_vote_change: false,
+            stipend: None,
+            stipend_claimed: LookupMap::new(StorageKey::StipendClaimed),
+            dismissed: UnorderedMap::new(StorageKey::Dismissed),
+            settlement: None,
+            migration: if old_state.prop_counter > 0 {
+                Some((1, old_state.prop_counter))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Converts up to `limit` stored proposals to the new layout, continuing from the last
+    /// call. Must be called until it returns true (all proposals are migrated).
+    #[private]
+    pub fn migrate_proposals(&mut self, limit: u32) -> bool {
+        let (next, last) = match self.migration {
+            Some(m) => m,
+            None => return true,
+        };
+        // same storage prefix. The old value is removed first, because `insert` deserializes
+        // the previous value.
+        let mut old_proposals: LookupMap<u32, OldProposal> = LookupMap::new(StorageKey::Proposals);
+        let end = last.min(next.saturating_add(limit).saturating_sub(1));
+        for id in next..=end {
+            if let Some(p) = old_proposals.remove(&id) {
+                self.proposals.insert(&id, &p.into());
+            }
         }
+        self.migration = if end < last {
+            Some((end + 1, last))
+        } else {
+            None
+        };
+        self.migration.is_none()
     }
 }
@@ -3,6 +3,7 @@ use near_sdk::json_types::{Base64VecU8, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, AccountId};
 
+use std::cmp::min;
 use std::collections::HashMap;
 
 use crate::VoteError;
@@ -35,22 +36,53 @@ pub struct Proposal {
     pub submission_time: u64,
     /// Unix time in miliseconds when the proposal reached approval threshold. `None` if it is not approved.
     pub approved_at: Option<u64>,
+    /// Houses which must co-approve the proposal before it can be executed.
+    pub co_approvers: Vec<AccountId>,
+    /// Co-approvers who already approved the proposal.
+    pub co_approved: Vec<AccountId>,
+    /// Proposals which must be executed before this proposal can be executed.
+    pub depends_on: Vec<u32>,
+    /// Unix time in miliseconds before which the proposal can't be executed.
+    pub not_before: Option<u64>,
+    /// Members who recused themselves from voting on this proposal. They are not counted in
+    /// the proposal electorate.
+    pub recused: Vec<AccountId>,
+    /// Amount of votes for which the proposer prepaid the storage.
+    pub reserved_votes: u8,
 }
 
 impl Proposal {
+    /// Adds the user vote to the tally. If `allow_change` is true, the user can overwrite
+    /// their previous vote with a different one. Re-casting the same vote is a `DoubleVote`
+    /// (so the approval time is not reset).
     pub fn add_vote(
         &mut self,
         user: AccountId,
         vote: Vote,
         threshold: u8,
+        allow_change: bool,
     ) -> Result<(), VoteError> {
-        if self.votes.contains_key(&user) {
-            return Err(VoteError::DoubleVote);
+        if self.recused.contains(&user) {
+            return Err(VoteError::Recused);
+        }
+        if let Some(old) = self.votes.get(&user) {
+            if !allow_change || std::mem::discriminant(&old.vote) == std::mem::discriminant(&vote) {
+                return Err(VoteError::DoubleVote);
+            }
+            match old.vote {
+                Vote::Approve => self.approve -= 1,
+                Vote::Reject => self.reject -= 1,
+                Vote::Abstain => self.abstain -= 1,
+            }
+            if self.approve < threshold {
+                self.approved_at = None;
+            }
         }
         match vote {
             Vote::Approve => {
                 self.approve += 1;
-                if self.approve >= threshold {
+                // keep the time when the threshold was crossed.
+                if self.approve >= threshold && self.approved_at.is_none() {
                     self.approved_at = Some(env::block_timestamp_ms());
                 }
             }
@@ -72,6 +104,16 @@ impl Proposal {
         Ok(())
     }
 
+    /// Returns the effective electorate (amount of members eligible to vote) and the effective
+    /// threshold, excluding members who recused themselves from voting.
+    pub fn electorate(&self, members_num: usize, threshold: u8) -> (usize, u8) {
+        if self.recused.is_empty() {
+            return (members_num, threshold);
+        }
+        let members_num = members_num.saturating_sub(self.recused.len());
+        (members_num, min(threshold, (members_num / 2) as u8 + 1))
+    }
+
     /// Returns true if it's past min voting duration
     pub fn finalize_status(
         &mut self,
@@ -83,12 +125,17 @@ impl Proposal {
         if !matches!(self.status, ProposalStatus::InProgress) {
             return true;
         }
+        let (members_num, threshold) = self.electorate(members_num, threshold);
         let past_min_vote_duration = self.past_min_vote_duration(min_vote_duration);
         let all_voted = self.votes.len() == members_num;
         if past_min_vote_duration || all_voted {
             if self.approve >= threshold {
                 self.status = ProposalStatus::Approved;
-            } else if self.reject + self.abstain > members_num as u8 - threshold
+                // threshold could be lowered by a recusal after the approval votes were cast.
+                if self.approved_at.is_none() {
+                    self.approved_at = Some(env::block_timestamp_ms());
+                }
+            } else if self.reject + self.abstain > (members_num as u8).saturating_sub(threshold)
                 || env::block_timestamp_ms() > self.submission_time + vote_duration
             {
                 self.status = ProposalStatus::Rejected;
@@ -97,6 +144,11 @@ impl Proposal {
         past_min_vote_duration
     }
 
+    /// Returns true if all required houses co-approved the proposal.
+    pub fn is_co_approved(&self) -> bool {
+        self.co_approvers.len() == self.co_approved.len()
+    }
+
     pub fn past_min_vote_duration(&self, min_vote_duration: u64) -> bool {
         if min_vote_duration == 0 {
             return true;
@@ -133,16 +185,35 @@ pub enum PropKind {
         member: AccountId,
         house: AccountId,
     },
+    /// Single funding request denominated in a NEP-141 token. When executed, `amount` of
+    /// `token` is transferred to the `receiver_id`.
+    FtFundingRequest {
+        token: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    },
+    /// Sets the monthly stipend of each member. Can be approved only once per term.
+    /// Members claim the stipend pro-rata for the time served (`claim_stipend`).
+    SetStipend {
+        amount_per_month: U128,
+    },
+    /// Budget which requires the Voting Body ratification. It's not executed with `execute`,
+    /// instead, the ratification (`ratify_budget_hook`) executes the proposal and raises the
+    /// `budget_cap` by the given amount.
+    Budget(U128),
 }
 
 impl PropKind {
     pub fn required_perm(&self) -> PropPerm {
         match self {
             PropKind::FunctionCall { .. } => PropPerm::FunctionCall,
-            PropKind::Text { .. } => PropPerm::Text,
+            PropKind::Text => PropPerm::Text,
             PropKind::FundingRequest { .. } => PropPerm::FundingRequest,
             PropKind::RecurrentFundingRequest { .. } => PropPerm::RecurrentFundingRequest,
             PropKind::DismissAndBan { .. } => PropPerm::DismissAndBan,
+            PropKind::FtFundingRequest { .. } => PropPerm::FtFundingRequest,
+            PropKind::SetStipend { .. } => PropPerm::SetStipend,
+            PropKind::Budget(_) => PropPerm::Budget,
         }
     }
 
@@ -150,10 +221,13 @@ impl PropKind {
     pub fn to_name(&self) -> String {
         match self {
             PropKind::FunctionCall { .. } => "function-call".to_string(),
-            PropKind::Text { .. } => "text".to_string(),
+            PropKind::Text => "text".to_string(),
             PropKind::FundingRequest { .. } => "funding-request".to_string(),
             PropKind::RecurrentFundingRequest { .. } => "recurrent-funding-request".to_string(),
             PropKind::DismissAndBan { .. } => "remove-and-ban".to_string(),
+            PropKind::FtFundingRequest { .. } => "ft-funding-request".to_string(),
+            PropKind::SetStipend { .. } => "set-stipend".to_string(),
+            PropKind::Budget(_) => "budget".to_string(),
         }
     }
 }
@@ -171,6 +245,8 @@ pub enum ProposalStatus {
     Failed,
     // note: In Astra++ we have also: Removed nor Moved
     Vetoed,
+    /// Proposal was open (in progress, approved or failed) when the house was dissolved.
+    Cancelled,
 }
 
 /// Votes recorded in the proposal.
@@ -193,6 +269,16 @@ pub struct VoteRecord {
     pub vote: Vote,
 }
 
+/// Returns true if the JSON value contains (at any depth) a string equal to `s`.
+pub fn json_contains_str(value: &serde_json::Value, s: &str) -> bool {
+    match value {
+        serde_json::Value::String(v) => v == s,
+        serde_json::Value::Array(arr) => arr.iter().any(|v| json_contains_str(v, s)),
+        serde_json::Value::Object(obj) => obj.values().any(|v| json_contains_str(v, s)),
+        _ => false,
+    }
+}
+
 /// Function call arguments.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -204,6 +290,14 @@ pub struct ActionCall {
     pub gas: U64,
 }
 
+impl ActionCall {
+    /// Decodes the function call arguments as JSON. Returns `None` if the arguments are not
+    /// a valid JSON.
+    pub fn args_json(&self) -> Option<serde_json::Value> {
+        serde_json::from_slice(&self.args.0).ok()
+    }
+}
+
 /// Permissions for creating proposals. See PropposalKind for more information.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(test, derive(Debug, Clone))]
@@ -214,6 +308,9 @@ pub enum PropPerm {
     FundingRequest,
     RecurrentFundingRequest,
     DismissAndBan,
+    FtFundingRequest,
+    SetStipend,
+    Budget,
 }
 
 /// Permissions for calling hooks
@@ -227,6 +324,15 @@ pub enum HookPerm {
     VetoBigOrReccurentFundingReq,
     Dismiss,
     Dissolve,
+    /// Allows to set the successor house (elected for the next term).
+    SetSuccessor,
+    /// Allows to hand over the state and funds to this house. Should be given to the
+    /// predecessor house.
+    Handover,
+    /// Allows to co-approve joint proposals, which list the caller as a co-approver.
+    CoApprove,
+    /// Allows to ratify budget proposals.
+    RatifyBudget,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize)]
@@ -234,4 +340,7 @@ pub enum HookPerm {
 #[serde(crate = "near_sdk::serde")]
 pub enum ExecRespErr {
     BudgetOverflow,
+    /// Not all required houses co-approved the proposal in time.
+    CoApprovalExpired,
+    StipendAlreadySet,
 }
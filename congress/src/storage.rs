@@ -1,5 +1,7 @@
-use near_sdk::borsh::{self, BorshSerialize};
-use near_sdk::BorshStorageKey;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{AccountId, BorshStorageKey};
 
 /// Helper structure for keys of the persistent collections.
 #[derive(BorshSerialize, BorshStorageKey)]
@@ -7,4 +9,99 @@ pub enum StorageKey {
     Proposals,
     Members,
     HookAuth,
+    Handover,
+    TokenBudgets,
+    MemberProposals,
+    Dismissed,
+    StipendClaimed,
+}
+
+/// Limits for creating proposals.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Default, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct ProposalLimits {
+    /// max length (in bytes) of the proposal description.
+    pub max_description_len: Option<u32>,
+    /// max amount of in progress proposals created by a single member.
+    pub max_open_proposals: Option<u32>,
+}
+
+/// Budget of a NEP-141 fungible token. All amounts are in the token base units.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq, Clone))]
+pub struct TokenBudget {
+    pub cap: U128,
+    pub spent: U128,
+    /// size of the big funding request, eligible for `VetoBigOrReccurentFundingReq`.
+    pub big_funding_threshold: U128,
+}
+
+/// Summary of the house state handed over to the successor house at the end of the term.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq, Clone))]
+pub struct HandoverSummary {
+    /// end of the predecessor term (unix time in milliseconds).
+    pub end_time: u64,
+    /// number of proposals created by the predecessor.
+    pub prop_counter: u32,
+    /// members at the time of dissolution.
+    pub members: Vec<AccountId>,
+    pub budget_cap: U128,
+    pub budget_spent: U128,
+    /// Approved recurrent funding requests, which were not executed by the predecessor.
+    pub recurrent_obligations: Vec<RecurrentObligation>,
+}
+
+/// State of the settlement, which is done in batches when the house is dissolved.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Settlement {
+    /// next proposal to settle.
+    pub next_prop: u32,
+    /// amount of open proposals closed with the `Cancelled` status.
+    pub cancelled: u32,
+    /// total reserved (and not used) vote storage refunded to proposers.
+    pub refunded: U128,
+    /// recurrent funding requests approved, but not executed, collected for the handover.
+    pub recurrent_obligations: Vec<RecurrentObligation>,
+    /// unclaimed stipends paid out to the members and dismissed members.
+    pub stipends_paid: U128,
+    pub finished: bool,
+    /// true once the remaining funds are swept. Further `settle` calls are no-op.
+    pub swept: bool,
+}
+
+impl Settlement {
+    pub fn new() -> Self {
+        Self {
+            next_prop: 1,
+            cancelled: 0,
+            refunded: U128(0),
+            recurrent_obligations: Vec::new(),
+            stipends_paid: U128(0),
+            finished: false,
+            swept: false,
+        }
+    }
+}
+
+impl Default for Settlement {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Recurrent funding request approved by a house, but not executed during its term.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct RecurrentObligation {
+    pub prop_id: u32,
+    pub proposer: AccountId,
+    /// size of the single month spending.
+    pub amount: U128,
 }
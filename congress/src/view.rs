@@ -16,6 +16,23 @@ pub struct ProposalOutput {
     pub id: u32,
     #[serde(flatten)]
     pub proposal: Proposal,
+    /// Reasons why the proposal can't be executed yet. Empty if there is nothing blocking the
+    /// proposal execution (other than the proposal status and the cooldown).
+    pub blocked: Vec<ExecError>,
+}
+
+/// This is format of output via JSON for the proposal which can be vetoed.
+#[derive(Serialize)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[cfg_attr(any(test, not(target_arch = "wasm32")), derive(Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct VetoableProposalOutput {
+    /// Id of the proposal.
+    pub id: u32,
+    /// Unix time in milliseconds until which the proposal can be vetoed.
+    pub veto_deadline: u64,
+    #[serde(flatten)]
+    pub proposal: Proposal,
 }
 
 /// This is format of output via JSON for the config.
@@ -31,6 +48,11 @@ pub struct ConfigOutput {
     pub budget_cap: U128,
     pub big_funding_threshold: U128,
     pub min_vote_duration: u64,
+    pub token_budgets: HashMap<AccountId, TokenBudget>,
+    pub proposal_limits: ProposalLimits,
+    pub allow_vote_change: bool,
+    /// monthly stipend of each member.
+    pub stipend: Option<U128>,
 }
 
 #[derive(Serialize)]
@@ -81,12 +103,44 @@ impl Contract {
                     self.min_vote_duration,
                     self.vote_duration,
                 );
-                ProposalOutput { id, proposal }
+                let blocked = self.exec_blockers(&proposal);
+                ProposalOutput {
+                    id,
+                    proposal,
+                    blocked,
+                }
             })
         })
         .collect()
     }
 
+    /// Returns proposals which can currently be vetoed by the `hook` (based on the `hook_auth`
+    /// permissions) together with the veto deadline.
+    /// Proposals are checked in paginated view: `limit` proposals are checked starting from
+    /// `from_index`, hence less than `limit` proposals can be returned.
+    pub fn vetoable_proposals(
+        &self,
+        hook: AccountId,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<VetoableProposalOutput> {
+        let from_index = max(from_index, 1);
+        (from_index..=min(self.prop_counter, from_index.saturating_add(limit) - 1))
+            .filter_map(|id| {
+                let mut proposal = self.proposals.get(&id)?;
+                let veto_deadline = self.veto_deadline(&mut proposal).ok()?;
+                if self.assert_veto_perm(&hook, &proposal).is_err() {
+                    return None;
+                }
+                Some(VetoableProposalOutput {
+                    id,
+                    veto_deadline,
+                    proposal,
+                })
+            })
+            .collect()
+    }
+
     /// Get specific proposal.
     pub fn get_proposal(&self, id: u32) -> Option<ProposalOutput> {
         let (members, _) = self.members.get().unwrap();
@@ -98,7 +152,12 @@ impl Contract {
                 self.min_vote_duration,
                 self.vote_duration,
             );
-            ProposalOutput { id, proposal }
+            let blocked = self.exec_blockers(&proposal);
+            ProposalOutput {
+                id,
+                proposal,
+                blocked,
+            }
         })
     }
 
@@ -151,6 +210,27 @@ impl Contract {
         self.hook_auth.get().unwrap()
     }
 
+    /// Returns the stipend earned by the member (or dismissed member) and not claimed yet.
+    /// Returns 0 if the stipend is not set or the account is not a member.
+    pub fn claimable_stipend(&self, member: AccountId) -> U128 {
+        U128(self.claimable_stipend_of(&member).unwrap_or(0))
+    }
+
+    /// Returns the settlement state of a dissolved house.
+    pub fn settlement(&self) -> Option<Settlement> {
+        self.settlement.clone()
+    }
+
+    /// Returns the house elected for the next term, if set.
+    pub fn successor(&self) -> Option<AccountId> {
+        self.successor.clone()
+    }
+
+    /// Returns the state summary handed over by the predecessor house.
+    pub fn handover(&self) -> Option<HandoverSummary> {
+        self.handover.get()
+    }
+
     pub fn config(&self) -> ConfigOutput {
         ConfigOutput {
             threshold: self.threshold,
@@ -162,6 +242,10 @@ impl Contract {
             budget_cap: U128(self.budget_cap),
             big_funding_threshold: U128(self.big_funding_threshold),
             min_vote_duration: self.min_vote_duration,
+            token_budgets: self.token_budgets.get().unwrap(),
+            proposal_limits: self.proposal_limits.clone(),
+            allow_vote_change: self.allow_vote_change,
+            stipend: self.stipend.map(U128),
         }
     }
 }
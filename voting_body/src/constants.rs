@@ -3,17 +3,56 @@ use near_sdk::{Balance, Gas, ONE_NEAR};
 pub const MILI_NEAR: Balance = ONE_NEAR / 1_000;
 /// 0.9N
 pub const SLASH_REWARD: Balance = 900 * MILI_NEAR;
+/// Default fee charged when the proposer withdraws a proposal. 0.1N
+pub const WITHDRAW_FEE: Balance = 100 * MILI_NEAR;
 
 /// Gas reserved for final failure callback which panics if one of the callback fails.
 pub const FAILURE_CALLBACK_GAS: Gas = Gas(3 * Gas::ONE_TERA.0);
 pub const EXECUTE_CALLBACK_GAS: Gas = Gas(4 * Gas::ONE_TERA.0);
 
+pub const CREATE_CALLBACK_GAS: Gas = Gas(10 * Gas::ONE_TERA.0);
+pub const POPULATION_CALLBACK_GAS: Gas = Gas(5 * Gas::ONE_TERA.0);
+
 pub const EXECUTE_GAS: Gas = Gas(8 * Gas::ONE_TERA.0);
 
-// 64bytes(accountID) + 1byte (prefix) + 4bytes(proposal_id) + vote(byte) = 72B -> add 20% margin = < 90B
-pub const VOTE_STORAGE: u64 = 90;
+// Every storage entry costs 40B + key + value. For a 64 bytes account ID (68B encoded):
+// vote record: 1B (prefix) + 4B (proposal_id) + 68B (voter) + 8B (timestamp) + 1B (vote)
+//   + 1B (delegate) = 123B, delegated vote: + 68B (delegate account ID) = 191B
+// voters index: 1B (prefix) + 4B (proposal_id) + 4B (index) + 68B (voter) = 117B
+// account votes index: 1B (prefix) + 68B (voter) + 4B (index) + 4B (proposal_id) = 117B
+// account votes length (first vote of the account): 1B (prefix) + 68B (voter) + 4B = 113B
+// vote index positions: 1B (prefix) + 4B (proposal_id) + 68B (voter) + 8B = 121B
+// = 659B, Rank vote: + 4B + up to MAX_RANKING bytes = 666B
+// new multiple choice ballot: 1B (prefix) + 4B (proposal_id) + 4B (index) + 4B + 3B (ranking)
+//   + 4B (votes) = 60B, ballot index: 1B (prefix) + 4B (proposal_id) + 4B + 3B (ranking)
+//   + 4B (index) = 56B
+// = 782B -> add 15% margin = < 900B
+pub const VOTE_STORAGE: u64 = 900;
+
+/// max amount of accounts which can delegate their votes to a single delegate. Every delegated
+/// vote is recorded when the delegate votes, so we need to limit it to fit in the gas limit.
+pub const MAX_DELEGATORS: usize = 100;
+
+/// max amount of options in a multiple choice proposal.
+pub const MAX_CHOICE_OPTIONS: usize = 10;
+/// max amount of options in a `Rank` ballot. Limits the number of distinct ballots of a
+/// proposal (720 + 90 + 10 for 10 options), which are loaded to tally the proposal.
+pub const MAX_RANKING: usize = 3;
+
+/// min approval threshold (percentage) of consents updated by the `UpdateConsent` proposal.
+pub const MIN_THRESHOLD: u8 = 50;
+/// max amount of supporters required to move a proposal to the active queue, which can be
+/// set by the `UpdatePreVoteSupport` proposal.
+pub const MAX_PRE_VOTE_SUPPORT: u32 = 10_000;
 
 /// max voting duration to prevent common mistake with time unit. 90 days in milliseconds
 pub const MAX_DURATION: u64 = 7776000000;
 /// min voting duration to prevent common mistake with time unit. 1 day in milliseconds
 pub const MIN_DURATION: u64 = 86400000;
+/// default reveal window of secret ballot proposals. 1 day in milliseconds
+pub const REVEAL_DURATION: u64 = MIN_DURATION;
+
+/// validity of the legacy IOM whitelist entries converted by `migrate`. 90 days in milliseconds
+pub const LEGACY_WHITELIST_DURATION: u64 = MAX_DURATION;
+/// reason (hashed) of the legacy IOM whitelist entries converted by `migrate`.
+pub const LEGACY_WHITELIST_REASON: &[u8] = b"legacy IOM whitelist";
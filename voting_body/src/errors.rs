@@ -11,6 +11,12 @@ pub enum VoteError {
     LockedUntil,
     Storage(String),
     NotIAHreg,
+    SecretBallot,
+    NotSecretBallot,
+    NotRevealTime,
+    NoCommitment,
+    BadReveal,
+    BadVote,
 }
 
 impl FunctionError for VoteError {
@@ -25,6 +31,12 @@ impl FunctionError for VoteError {
             }
             VoteError::Storage(reason) => panic_str(reason),
             VoteError::NotIAHreg => panic_str("must be called by iah_registry"),
+            VoteError::SecretBallot => panic_str("secret ballot proposal, use commit_vote"),
+            VoteError::NotSecretBallot => panic_str("proposal doesn't use secret ballot"),
+            VoteError::NotRevealTime => panic_str("reveal window is not open"),
+            VoteError::NoCommitment => panic_str("vote was not committed"),
+            VoteError::BadReveal => panic_str("vote and salt don't match the commitment"),
+            VoteError::BadVote => panic_str("vote is not valid for the proposal kind"),
         }
     }
 }
@@ -36,6 +48,8 @@ pub enum ExecError {
     PropNotFound,
     AlreadyFinalized,
     InProgress,
+    /// a constitution with the same or greater version was already ratified.
+    StaleConstitution,
 }
 
 impl FunctionError for ExecError {
@@ -44,6 +58,9 @@ impl FunctionError for ExecError {
             ExecError::PropNotFound => panic_str("proposal doesn't exist"),
             ExecError::AlreadyFinalized => panic_str("proposal is already successfully finalized"),
             ExecError::InProgress => panic_str("proposal is still in progress"),
+            ExecError::StaleConstitution => {
+                panic_str("constitution version must be greater than the current one")
+            }
         }
     }
 }
@@ -69,6 +86,65 @@ impl FunctionError for CreatePropError {
     }
 }
 
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Debug))]
+pub enum DelegateError {
+    NotIAHreg,
+    SelfDelegation,
+    TooManyDelegators,
+    NotDelegated,
+    NotAccepted,
+    Storage(String),
+}
+
+impl FunctionError for DelegateError {
+    fn panic(&self) -> ! {
+        match self {
+            DelegateError::NotIAHreg => panic_str("must be called by iah_registry"),
+            DelegateError::SelfDelegation => panic_str("can't delegate to self"),
+            DelegateError::TooManyDelegators => panic_str("delegate has too many delegators"),
+            DelegateError::NotDelegated => panic_str("account didn't delegate"),
+            DelegateError::NotAccepted => panic_str("delegate doesn't accept delegations"),
+            DelegateError::Storage(reason) => panic_str(reason),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Debug))]
+pub enum ReclaimError {
+    PropNotFound,
+    NotFinalized,
+}
+
+impl FunctionError for ReclaimError {
+    fn panic(&self) -> ! {
+        match self {
+            ReclaimError::PropNotFound => panic_str("proposal doesn't exist"),
+            ReclaimError::NotFinalized => panic_str("voting is not finished"),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Debug))]
+pub enum WithdrawError {
+    NotFound,
+    NotProposer,
+    Overdue,
+    AlreadyVoted,
+}
+
+impl FunctionError for WithdrawError {
+    fn panic(&self) -> ! {
+        match self {
+            WithdrawError::NotFound => panic_str("proposal not found"),
+            WithdrawError::NotProposer => panic_str("only proposer can withdraw the proposal"),
+            WithdrawError::Overdue => panic_str("pre-vote proposal is overdue"),
+            WithdrawError::AlreadyVoted => {
+                panic_str("proposal can't be withdrawn once the voting started")
+            }
+        }
+    }
+}
+
 #[cfg_attr(not(target_arch = "wasm32"), derive(PartialEq, Debug))]
 pub enum PrevoteError {
     NotFound,
@@ -79,6 +155,7 @@ pub enum PrevoteError {
     NotCongressMember,
     LockedUntil,
     NotIAHreg,
+    NotSupported,
 }
 
 impl FunctionError for PrevoteError {
@@ -94,6 +171,7 @@ impl FunctionError for PrevoteError {
                 panic_str("account must be locked in iah_registry longer than the prevote end")
             }
             PrevoteError::NotIAHreg => panic_str("must be called by iah_registry"),
+            PrevoteError::NotSupported => panic_str("proposal is not supported by the caller"),
         }
     }
 }
@@ -1,7 +1,12 @@
-use near_sdk::{json_types::U128, serde::Serialize, Balance};
+use near_sdk::{
+    json_types::{Base64VecU8, U128},
+    serde::Serialize,
+    AccountId, Balance,
+};
 use serde_json::json;
 
-use crate::proposal::PropKind;
+use crate::proposal::{PropKind, Role, Vote};
+use crate::storage::WhitelistEntry;
 
 use common::{EventPayload, NearEvent};
 
@@ -38,6 +43,22 @@ pub(crate) fn emit_prevote_prop_slashed(prop_id: u32, bond: Balance) {
     });
 }
 
+/// Emitted when a user withdraws the pre-vote support.
+pub(crate) fn emit_prop_unsupport(prop_id: u32, account: &AccountId) {
+    emit_event(EventPayload {
+        event: "proposal-unsupport",
+        data: json!({ "prop_id": prop_id, "account": account }),
+    });
+}
+
+/// Emitted when the proposer withdraws a proposal.
+pub(crate) fn emit_prop_withdraw(prop_id: u32, refund: Balance, fee: Balance) {
+    emit_event(EventPayload {
+        event: "proposal-withdraw",
+        data: json!({ "prop_id": prop_id, "refund": U128(refund), "fee": U128(fee) }),
+    });
+}
+
 pub(crate) fn emit_prop_slashed(prop_id: u32, bond: Balance) {
     emit_event(EventPayload {
         event: "proposal-slash",
@@ -45,9 +66,19 @@ pub(crate) fn emit_prop_slashed(prop_id: u32, bond: Balance) {
     });
 }
 
-pub(crate) fn emit_vote(prop_id: u32) {
+/// `delegators`: accounts for which the vote was recorded by the voter as their delegate.
+/// Events record votes in the `votes_hash_chain` order.
+pub(crate) fn emit_vote(prop_id: u32, voter: &AccountId, vote: &Vote, delegators: &[AccountId]) {
     emit_event(EventPayload {
         event: "vote",
+        data: json!({ "prop_id": prop_id, "voter": voter, "vote": vote, "delegators": delegators }),
+    });
+}
+
+/// Emitted when a vote commitment is submitted for a secret ballot proposal.
+pub(crate) fn emit_vote_commit(prop_id: u32) {
+    emit_event(EventPayload {
+        event: "vote-commit",
         data: json!({ "prop_id": prop_id }),
     });
 }
@@ -66,3 +97,65 @@ pub(crate) fn emit_spam(prop_id: u32) {
         data: json!({ "prop_id": prop_id }),
     });
 }
+
+pub(crate) fn emit_delegate(delegator: &AccountId, delegate: &AccountId) {
+    emit_event(EventPayload {
+        event: "delegate",
+        data: json!({ "delegator": delegator, "delegate": delegate }),
+    });
+}
+
+pub(crate) fn emit_undelegate(delegator: &AccountId, delegate: &AccountId) {
+    emit_event(EventPayload {
+        event: "undelegate",
+        data: json!({ "delegator": delegator, "delegate": delegate }),
+    });
+}
+
+pub(crate) fn emit_constitution_ratify(prop_id: u32, version: u32, document_hash: &Base64VecU8) {
+    emit_event(EventPayload {
+        event: "constitution-ratify",
+        data: json!({ "prop_id": prop_id, "version": version, "document_hash": document_hash }),
+    });
+}
+
+pub(crate) fn emit_role_grant(prop_id: u32, account: &AccountId, role: Role) {
+    emit_event(EventPayload {
+        event: "role-grant",
+        data: json!({ "prop_id": prop_id, "account": account, "role": role }),
+    });
+}
+
+pub(crate) fn emit_role_revoke(prop_id: u32, account: &AccountId, role: Role) {
+    emit_event(EventPayload {
+        event: "role-revoke",
+        data: json!({ "prop_id": prop_id, "account": account, "role": role }),
+    });
+}
+
+pub(crate) fn emit_whitelist_add(account: &AccountId, entry: &WhitelistEntry) {
+    emit_event(EventPayload {
+        event: "whitelist-add",
+        data: json!({
+            "account": account,
+            "expires_at": entry.expires_at,
+            "reason": entry.reason,
+            "authority": entry.authority,
+        }),
+    });
+}
+
+pub(crate) fn emit_whitelist_remove(account: &AccountId, authority: &AccountId) {
+    emit_event(EventPayload {
+        event: "whitelist-remove",
+        data: json!({ "account": account, "authority": authority }),
+    });
+}
+
+/// Emitted when the IAH population is updated from the registry.
+pub(crate) fn emit_population_update(count: u64) {
+    emit_event(EventPayload {
+        event: "population-update",
+        data: json!({ "count": count }),
+    });
+}
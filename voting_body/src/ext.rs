@@ -1,11 +1,21 @@
 use common::errors::HookError;
 use near_sdk::ext_contract;
+use near_sdk::json_types::U128;
 use near_sdk::AccountId;
 
+use crate::types::{CongressProposal, CreatePropPayload, VetoableProposal};
+
 #[ext_contract(ext_self)]
 pub trait ExtSelf {
     fn on_execute(&mut self, prop_id: u32);
     fn on_support_by_congress(&mut self, prop_id: u32);
+    fn on_population_update(&mut self);
+    fn on_create_proposal(&mut self, caller: AccountId, bond: U128, payload: CreatePropPayload);
+}
+
+#[ext_contract(ext_registry)]
+pub trait ExtRegistry {
+    fn sbt_supply_by_class(&self, issuer: AccountId, class: u64) -> u64;
 }
 
 #[ext_contract(ext_congress)]
@@ -13,5 +23,13 @@ pub trait ExtCongress {
     fn veto_hook(&mut self, id: u32) -> Result<(), HookError>;
     fn dissolve_hook(&mut self) -> Result<(), HookError>;
     fn dismiss_hook(&mut self, member: AccountId) -> Result<(), HookError>;
+    fn ratify_budget_hook(&mut self, id: u32) -> Result<(), HookError>;
     fn is_member(&self, account: AccountId) -> bool;
+    fn get_proposal(&self, id: u32) -> Option<CongressProposal>;
+    fn vetoable_proposals(
+        &self,
+        hook: AccountId,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<VetoableProposal>;
 }
@@ -1,12 +1,57 @@
+use std::cmp::min;
+
 use super::*;
 
+/// Verifies the congress response for a proposal targeting a congress house:
+/// * `Dismiss`: the member is part of the house (`is_member`);
+/// * `Veto`: the proposal can be vetoed by the Voting Body (`vetoable_proposals`);
+/// * `ApproveBudget`: the proposal is an approved `Budget` proposal (`get_proposal`).
+pub(crate) fn verify_congress_response(kind: &PropKind, resp: &[u8]) -> bool {
+    match kind {
+        PropKind::Dismiss { .. } => serde_json::from_slice::<bool>(resp).unwrap_or(false),
+        PropKind::Veto { prop_id, .. } => serde_json::from_slice::<Vec<VetoableProposal>>(resp)
+            .map_or(false, |props| props.iter().any(|p| p.id == *prop_id)),
+        PropKind::ApproveBudget { .. } => {
+            match serde_json::from_slice::<Option<CongressProposal>>(resp) {
+                Ok(Some(p)) => {
+                    matches!(p.status, CongressPropStatus::Approved)
+                        && p.kind.get("Budget").is_some()
+                }
+                _ => false,
+            }
+        }
+        _ => true,
+    }
+}
+
+/// Returns true if the proposal is verified in the congress (see `verify_congress_response`).
+pub(crate) fn is_verified_in_congress(kind: &PropKind) -> bool {
+    matches!(
+        kind,
+        PropKind::Dismiss { .. } | PropKind::Veto { .. } | PropKind::ApproveBudget { .. }
+    )
+}
+
+/// Parses the congress `is_member` response.
+pub(crate) fn is_member_response(result: PromiseResult) -> bool {
+    match result {
+        PromiseResult::Successful(resp) => serde_json::from_slice::<bool>(&resp).unwrap_or(false),
+        _ => false,
+    }
+}
+
 impl Contract {
     /**********
      * TRANSACTIONS
      **********/
 
     /// Creates a new proposal.
-    /// Returns the new proposal ID.
+    /// Returns the new proposal ID. Proposals targeting a congress proposal or a congress
+    /// member (`Veto`, `ApproveBudget`, `Dismiss`) are verified in the congress house first: a
+    /// promise is returned and the proposal is created in the `on_create_proposal` callback.
+    /// Similarly, when the bond is below `active_queue_bond`, the congress houses are queried
+    /// if the caller is a congress member: proposals of congress members go directly to the
+    /// active queue.
     /// Caller is required to attach enough deposit to cover the proposal storage as well as all
     /// possible votes.
     /// NOTE: storage is paid from the bond.
@@ -15,9 +60,8 @@ impl Contract {
         &mut self,
         caller: AccountId,
         payload: CreatePropPayload,
-    ) -> Result<u32, CreatePropError> {
+    ) -> Result<PromiseOrValue<u32>, CreatePropError> {
         let storage_start = env::storage_usage();
-        let now = env::block_timestamp_ms();
         let bond = env::attached_deposit();
 
         if bond < self.pre_vote_bond {
@@ -25,42 +69,168 @@ impl Contract {
         }
 
         // validate proposals
+        let accounts = self.accounts.get().unwrap();
         match &payload.kind {
-            PropKind::FunctionCall { receiver_id, .. } => {
-                let accounts = self.accounts.get().unwrap();
-                if *receiver_id == accounts.congress_coa
-                    || *receiver_id == accounts.congress_hom
-                    || *receiver_id == accounts.congress_tc
-                {
-                    return Err(CreatePropError::BadRequest(
+            PropKind::FunctionCall { receiver_id, .. } if accounts.is_congress(receiver_id) => {
+                return Err(CreatePropError::BadRequest(
                     "receiver_id can't be a congress house, use a specific proposal to interact with the congress".to_string(),
                 ));
-                }
             }
             PropKind::UpdateVoteDuration {
                 pre_vote_duration,
                 vote_duration,
+            } if *pre_vote_duration < MIN_DURATION
+                || *vote_duration < MIN_DURATION
+                || *pre_vote_duration > MAX_DURATION
+                || *vote_duration > MAX_DURATION =>
+            {
+                return Err(CreatePropError::BadRequest(
+                    "receiver_id can't be a congress house, use a specific proposal to interact with the congress".to_string(),
+                ));
+            }
+            PropKind::MultipleChoice { options, .. }
+                if options.len() < 2 || options.len() > MAX_CHOICE_OPTIONS =>
+            {
+                return Err(CreatePropError::BadRequest(format!(
+                    "multiple choice proposal must have 2-{} options",
+                    MAX_CHOICE_OPTIONS
+                )));
+            }
+            PropKind::UpdateConsent {
+                simple_consent: simple,
+                super_consent: sup,
             } => {
-                if *pre_vote_duration < MIN_DURATION
-                    || *vote_duration < MIN_DURATION
-                    || *pre_vote_duration > MAX_DURATION
-                    || *vote_duration > MAX_DURATION
+                if !simple.verify()
+                    || !sup.verify()
+                    || simple.threshold < MIN_THRESHOLD
+                    || simple.quorum == 0
+                {
+                    return Err(CreatePropError::BadRequest(format!(
+                        "consent threshold must be a percentage ({}-100%) and quorum must be positive",
+                        MIN_THRESHOLD
+                    )));
+                }
+                if sup.threshold < simple.threshold
+                    || sup.quorum < simple.quorum
+                    || sup.quorum_percent.unwrap_or(0) < simple.quorum_percent.unwrap_or(0)
                 {
                     return Err(CreatePropError::BadRequest(
-                    "receiver_id can't be a congress house, use a specific proposal to interact with the congress".to_string(),
+                        "super consent can't be weaker than simple consent".to_string(),
+                    ));
+                }
+            }
+            PropKind::UpdatePreVoteSupport { pre_vote_support }
+                if *pre_vote_support == 0 || *pre_vote_support > MAX_PRE_VOTE_SUPPORT =>
+            {
+                return Err(CreatePropError::BadRequest(format!(
+                    "pre_vote_support must be between 1 and {}",
+                    MAX_PRE_VOTE_SUPPORT
+                )));
+            }
+            PropKind::GrantRole { account, role } if self.has_role(account, *role) => {
+                return Err(CreatePropError::BadRequest(
+                    "account already has the role".to_string(),
                 ));
+            }
+            PropKind::RevokeRole { account, role } if !self.has_role(account, *role) => {
+                return Err(CreatePropError::BadRequest(
+                    "account doesn't have the role".to_string(),
+                ));
+            }
+            PropKind::RatifyConstitution {
+                document_hash,
+                uri,
+                version,
+            } => {
+                if document_hash.0.is_empty() || uri.is_empty() {
+                    return Err(CreatePropError::BadRequest(
+                        "constitution document_hash and uri must not be empty".to_string(),
+                    ));
                 }
+                if *version <= self.constitution_version() {
+                    return Err(CreatePropError::BadRequest(
+                        "constitution version must be greater than the current one".to_string(),
+                    ));
+                }
+            }
+            PropKind::Dismiss { dao, .. }
+            | PropKind::Dissolve { dao }
+            | PropKind::Veto { dao, .. }
+            | PropKind::ApproveBudget { dao, .. }
+                if !accounts.is_congress(dao) =>
+            {
+                return Err(CreatePropError::BadRequest(
+                    "dao must be a congress house".to_string(),
+                ));
             }
             _ => (),
         }
 
-        // TODO: check if proposal is created by a congress member. If yes, move it to active
-        // immediately.
-        let active = bond >= self.active_queue_bond;
+        // verify the target in the congress
+        let verify = match &payload.kind {
+            PropKind::Dismiss { dao, member } => {
+                Some(ext_congress::ext(dao.clone()).is_member(member.clone()))
+            }
+            PropKind::Veto { dao, prop_id } => {
+                Some(ext_congress::ext(dao.clone()).vetoable_proposals(
+                    env::current_account_id(),
+                    *prop_id,
+                    1,
+                ))
+            }
+            PropKind::ApproveBudget { dao, prop_id } => {
+                Some(ext_congress::ext(dao.clone()).get_proposal(*prop_id))
+            }
+            _ => None,
+        };
+        let verify = if bond < self.active_queue_bond {
+            let is_member =
+                |dao: &AccountId| ext_congress::ext(dao.clone()).is_member(caller.clone());
+            let members = is_member(&accounts.congress_hom)
+                .and(is_member(&accounts.congress_coa))
+                .and(is_member(&accounts.congress_tc));
+            Some(match verify {
+                Some(verify) => verify.and(members),
+                None => members,
+            })
+        } else {
+            verify
+        };
+        if let Some(verify) = verify {
+            return Ok(verify
+                .then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(CREATE_CALLBACK_GAS)
+                        .on_create_proposal(caller, U128(bond), payload),
+                )
+                .into());
+        }
+
+        let id = self.insert_proposal(caller.clone(), bond, payload, false);
+        match finalize_storage_check(storage_start, 0, caller) {
+            Err(reason) => Err(CreatePropError::Storage(reason)),
+            Ok(required) => {
+                self.finalize_proposal(id, required);
+                Ok(PromiseOrValue::Value(id))
+            }
+        }
+    }
+
+    /// Inserts a new proposal to the active queue (if `bond >= active_queue_bond` or the
+    /// caller is a congress member) or to the pre-vote queue. Returns the new proposal ID.
+    /// The proposal must be completed with `finalize_proposal` (or removed with
+    /// `remove_new_proposal`).
+    pub(crate) fn insert_proposal(
+        &mut self,
+        caller: AccountId,
+        bond: Balance,
+        payload: CreatePropPayload,
+        congress_member: bool,
+    ) -> u32 {
+        let active = congress_member || bond >= self.active_queue_bond;
         self.prop_counter += 1;
-        emit_prop_created(self.prop_counter, &payload.kind, active);
         let mut prop = Proposal {
-            proposer: caller.clone(),
+            proposer: caller,
             bond,
             additional_bond: None,
             description: payload.description,
@@ -76,27 +246,49 @@ impl Contract {
             spam: 0,
             support: 0,
             supported: HashSet::new(),
-            start: now,
+            start: env::block_timestamp_ms(),
             executed_at: None,
             proposal_storage: 0,
+            quorum: None,
+            votes_hash_chain: vec![0; 32].into(),
+            voters: 0,
+            reveal_duration: if payload.secret_ballot {
+                Some(self.reveal_duration)
+            } else {
+                None
+            },
+            commits: 0,
+            ballots: 0,
         };
         if active {
+            prop.quorum = Some(self.active_quorum(&prop));
             self.proposals.insert(&self.prop_counter, &prop);
         } else {
             self.pre_vote_proposals.insert(&self.prop_counter, &prop);
         }
+        self.prop_counter
+    }
 
-        prop.proposal_storage = match finalize_storage_check(storage_start, 0, caller) {
-            Err(reason) => return Err(CreatePropError::Storage(reason)),
-            Ok(required) => required,
-        };
-        if active {
-            self.proposals.insert(&self.prop_counter, &prop);
-        } else {
-            self.pre_vote_proposals.insert(&self.prop_counter, &prop);
+    /// Sets the storage paid for the proposal and emits the proposal create event.
+    pub(crate) fn finalize_proposal(&mut self, id: u32, proposal_storage: Balance) {
+        if let Some(mut prop) = self.proposals.get(&id) {
+            prop.proposal_storage = proposal_storage;
+            self.proposals.insert(&id, &prop);
+            emit_prop_created(id, &prop.kind, true);
+        } else if let Some(mut prop) = self.pre_vote_proposals.get(&id) {
+            prop.proposal_storage = proposal_storage;
+            self.pre_vote_proposals.insert(&id, &prop);
+            emit_prop_created(id, &prop.kind, false);
         }
+    }
 
-        Ok(self.prop_counter)
+    /// Removes the most recently inserted proposal (used when its creation fails in a callback).
+    pub(crate) fn remove_new_proposal(&mut self, id: u32) {
+        debug_assert_eq!(id, self.prop_counter);
+        if self.proposals.remove(&id).is_none() {
+            self.pre_vote_proposals.remove(&id);
+        }
+        self.prop_counter -= 1;
     }
 
     /// Supports proposal in the pre-vote queue.
@@ -131,6 +323,24 @@ impl Contract {
         Ok(true)
     }
 
+    pub fn unsupport_proposal_impl(
+        &mut self,
+        caller: AccountId,
+        payload: u32,
+    ) -> Result<bool, PrevoteError> {
+        let prop_id = payload;
+        let mut p = self.assert_pre_vote_prop(prop_id)?;
+        if env::block_timestamp_ms() - p.start > self.pre_vote_duration {
+            self.slash_prop(prop_id, p.bond);
+            self.pre_vote_proposals.remove(&prop_id);
+            return Ok(false);
+        }
+        p.remove_support(&caller)?;
+        self.pre_vote_proposals.insert(&prop_id, &p);
+        emit_prop_unsupport(prop_id, &caller);
+        Ok(true)
+    }
+
     pub fn vote_impl(
         &mut self,
         caller: AccountId,
@@ -145,23 +355,209 @@ impl Contract {
         if !matches!(prop.status, ProposalStatus::InProgress) {
             return Err(VoteError::NotInProgress);
         }
+        if prop.reveal_duration.is_some() {
+            return Err(VoteError::SecretBallot);
+        }
         if !prop.is_active(self.vote_duration) {
             return Err(VoteError::Timeout);
         }
         if locked_until <= prop.start + self.vote_duration {
             return Err(VoteError::LockedUntil);
         }
+        prop.check_vote(&payload.vote)?;
 
-        self.add_vote(payload.prop_id, caller.clone(), payload.vote, &mut prop);
+        self.add_vote(
+            payload.prop_id,
+            caller.clone(),
+            payload.vote.clone(),
+            None,
+            &mut prop,
+        );
+        // record delegated votes for delegators who didn't vote directly, are locked until the
+        // voting end and have enough deposit to pay the vote storage.
+        let voting_end = prop.start + self.vote_duration;
+        let vote_cost = VOTE_STORAGE as Balance * env::storage_byte_cost();
+        let mut delegated = Vec::new();
+        let mut delegated_storage: i64 = 0;
+        for d in self.delegators.get(&caller).unwrap_or_default() {
+            let mut delegation = match self.delegations.get(&d) {
+                Some(x) if x.locked_until > voting_end && x.deposit.0 >= vote_cost => x,
+                _ => continue,
+            };
+            let direct = self
+                .votes
+                .get(&(payload.prop_id, d.clone()))
+                .map_or(false, |v| v.delegate.is_none());
+            if direct {
+                continue;
+            }
+            let storage = env::storage_usage();
+            self.add_vote(
+                payload.prop_id,
+                d.clone(),
+                payload.vote.clone(),
+                Some(caller.clone()),
+                &mut prop,
+            );
+            // delegated vote storage is paid from the delegator deposit
+            let used = env::storage_usage() as i64 - storage as i64;
+            let amount = used.unsigned_abs() as Balance * env::storage_byte_cost();
+            if used > 0 {
+                delegation.deposit.0 -= amount;
+            } else {
+                delegation.deposit.0 += amount;
+            }
+            self.delegations.insert(&d, &delegation);
+            delegated_storage += used;
+            delegated.push(d);
+        }
         // NOTE: we can't quickly set a status to a finalized one because we don't know the total number of
         // voters
 
         self.proposals.insert(&payload.prop_id, &prop);
-        emit_vote(payload.prop_id);
+        emit_vote(payload.prop_id, &caller, &payload.vote, &delegated);
+
+        // delegated votes are paid by the delegators, and a direct vote overwriting a delegated
+        // vote releases storage
+        let storage_start = (storage_start as i64 + delegated_storage) as u64;
+        let storage_start = min(storage_start, env::storage_usage());
+        if let Err(reason) = finalize_storage_check(storage_start, 0, caller) {
+            return Err(VoteError::Storage(reason));
+        }
+        Ok(())
+    }
+
+    /// Commits a secret ballot vote. Overwrites the previous commitment of the `caller`.
+    pub fn commit_vote_impl(
+        &mut self,
+        caller: AccountId,
+        locked_until: u64,
+        payload: CommitPayload,
+    ) -> Result<(), VoteError> {
+        let storage_start = env::storage_usage();
+        let mut prop = self
+            .proposals
+            .get(&payload.prop_id)
+            .ok_or(VoteError::PropNotFound)?;
+        if !matches!(prop.status, ProposalStatus::InProgress) {
+            return Err(VoteError::NotInProgress);
+        }
+        if prop.reveal_duration.is_none() {
+            return Err(VoteError::NotSecretBallot);
+        }
+        let voting_end = prop.start + self.vote_duration;
+        if env::block_timestamp_ms() > voting_end {
+            return Err(VoteError::Timeout);
+        }
+        if locked_until <= voting_end {
+            return Err(VoteError::LockedUntil);
+        }
+
+        if self
+            .commitments
+            .insert(&(payload.prop_id, caller.clone()), &payload.commitment.0)
+            .is_none()
+        {
+            prop.commits += 1;
+            self.proposals.insert(&payload.prop_id, &prop);
+        }
+        emit_vote_commit(payload.prop_id);
 
         if let Err(reason) = finalize_storage_check(storage_start, 0, caller) {
             return Err(VoteError::Storage(reason));
         }
         Ok(())
     }
+
+    /// Reveals a committed secret ballot vote and records it.
+    pub fn reveal_vote_impl(
+        &mut self,
+        caller: AccountId,
+        prop_id: u32,
+        vote: Vote,
+        salt: Vec<u8>,
+    ) -> Result<(), VoteError> {
+        let storage_start = env::storage_usage();
+        let mut prop = self
+            .proposals
+            .get(&prop_id)
+            .ok_or(VoteError::PropNotFound)?;
+        if !matches!(prop.status, ProposalStatus::InProgress) {
+            return Err(VoteError::NotInProgress);
+        }
+        if prop.reveal_duration.is_none() {
+            return Err(VoteError::NotSecretBallot);
+        }
+        if env::block_timestamp_ms() <= prop.start + self.vote_duration
+            || !prop.is_active(self.vote_duration)
+        {
+            return Err(VoteError::NotRevealTime);
+        }
+        let key = (prop_id, caller.clone());
+        let commitment = self.commitments.get(&key).ok_or(VoteError::NoCommitment)?;
+        let mut data = (&caller, &vote).try_to_vec().unwrap();
+        data.extend(salt);
+        if env::sha256(&data) != commitment {
+            return Err(VoteError::BadReveal);
+        }
+        prop.check_vote(&vote)?;
+
+        self.commitments.remove(&key);
+        prop.commits -= 1;
+        self.add_vote(prop_id, caller.clone(), vote.clone(), None, &mut prop);
+        self.proposals.insert(&prop_id, &prop);
+        emit_vote(prop_id, &caller, &vote, &[]);
+
+        let storage_start = min(storage_start, env::storage_usage());
+        if let Err(reason) = finalize_storage_check(storage_start, 0, caller) {
+            return Err(VoteError::Storage(reason));
+        }
+        Ok(())
+    }
+
+    /// Delegates `caller` votes to the `delegate`. Overwrites the previous delegation.
+    /// The attached deposit, minus the delegation storage, is kept in the delegation to pay the
+    /// storage of delegated votes.
+    pub fn delegate_impl(
+        &mut self,
+        caller: AccountId,
+        locked_until: u64,
+        delegate: AccountId,
+    ) -> Result<(), DelegateError> {
+        let storage_start = env::storage_usage();
+        if caller == delegate {
+            return Err(DelegateError::SelfDelegation);
+        }
+        if !self.delegates.contains(&delegate) {
+            return Err(DelegateError::NotAccepted);
+        }
+        self.remove_delegation(&caller);
+        let mut delegators = self.delegators.get(&delegate).unwrap_or_default();
+        if delegators.len() >= MAX_DELEGATORS {
+            return Err(DelegateError::TooManyDelegators);
+        }
+        delegators.push(caller.clone());
+        self.delegators.insert(&delegate, &delegators);
+        let mut delegation = Delegation {
+            delegate: delegate.clone(),
+            locked_until,
+            deposit: U128(0),
+        };
+        self.delegations.insert(&caller, &delegation);
+        emit_delegate(&caller, &delegate);
+
+        // storage can be released when moving from a delegate with a bigger list
+        let storage_start = min(storage_start, env::storage_usage());
+        let required = (env::storage_usage() - storage_start) as Balance * env::storage_byte_cost();
+        let deposit = env::attached_deposit();
+        if deposit < required {
+            return Err(DelegateError::Storage(format!(
+                "not enough NEAR storage deposit, required: {}",
+                required
+            )));
+        }
+        delegation.deposit = U128(deposit - required);
+        self.delegations.insert(&caller, &delegation);
+        Ok(())
+    }
 }
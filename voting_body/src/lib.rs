@@ -1,17 +1,19 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use common::finalize_storage_check;
 use events::*;
 use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::{LazyOption, LookupMap},
+    collections::{LazyOption, LookupMap, LookupSet, UnorderedMap, Vector},
     env::{self, panic_str},
-    json_types::U128,
-    near_bindgen, require,
-    store::LookupSet,
-    AccountId, Balance, FunctionError, Gas, PanicOnDefault, Promise, PromiseOrValue, PromiseResult,
+    json_types::{Base64VecU8, U128},
+    near_bindgen, require, AccountId, Balance, FunctionError, Gas, PanicOnDefault, Promise,
+    PromiseOrValue, PromiseResult,
+};
+use types::{
+    CommitPayload, CongressPropStatus, CongressProposal, CreatePropPayload, ExecResponse, SBTs,
+    VetoableProposal, VotePayload,
 };
-use types::{CreatePropPayload, ExecResponse, SBTs, VotePayload};
 
 mod constants;
 mod errors;
@@ -61,7 +63,74 @@ pub struct Contract {
     /// Workaround for removing people from iom registry blacklist
     /// As we don't have a way to remove people from the blacklist, we can add them to the whitelist
     /// and allow them to vote directly.
-    pub iom_whitelist: LookupSet<AccountId>,
+    /// map account -> whitelist entry. Expired entries are not accepted.
+    pub whitelist: UnorderedMap<AccountId, WhitelistEntry>,
+
+    /// Fee charged (and sent to the community treasury) when the proposer withdraws a
+    /// proposal.
+    pub withdraw_fee: Balance,
+
+    /// IAH population used to compute the quorum of consents with `quorum_percent`.
+    /// `None` until the admin sets the SBT class of verified humans.
+    pub population: Option<Population>,
+
+    /// Voters index: map (prop_id, index) -> voter. Proposal `voters` is the index length.
+    pub voters: LookupMap<(u32, u32), AccountId>,
+    /// Votes index: map (voter, index) -> prop_id.
+    pub account_votes: LookupMap<(AccountId, u32), u32>,
+    /// map voter -> length of the voter votes index.
+    pub account_votes_len: LookupMap<AccountId, u32>,
+    /// map (prop_id, voter) -> positions of the vote in the `voters` and `account_votes`
+    /// indexes. Used to remove the index entries when the vote record is removed.
+    pub vote_index: LookupMap<(u32, AccountId), (u32, u32)>,
+
+    /// Reveal window (in milliseconds) of new secret ballot proposals.
+    pub reveal_duration: u64,
+    /// Secret ballot vote commitments: map (prop_id, voter) -> commitment.
+    pub commitments: LookupMap<(u32, AccountId), Vec<u8>>,
+
+    /// History of ratified constitutions, ordered by version. The last one is the current
+    /// constitution.
+    pub constitutions: Vector<Constitution>,
+
+    /// map account -> roles granted by the Voting Body.
+    pub roles: LazyOption<HashMap<AccountId, Vec<Role>>>,
+
+    /// map delegator -> Delegation
+    pub delegations: LookupMap<AccountId, Delegation>,
+    /// map delegate -> list of delegators
+    pub delegators: LookupMap<AccountId, Vec<AccountId>>,
+    /// accounts which accept vote delegations.
+    pub delegates: LookupSet<AccountId>,
+
+    /// Multiple choice ballots index: map (prop_id, index) -> (ranking, number of votes).
+    /// Proposal `ballots` is the index length. Ballots without votes are kept in the index.
+    pub ballots: LookupMap<(u32, u32), (Vec<u8>, u32)>,
+    /// map (prop_id, ranking) -> position of the ballot in the `ballots` index.
+    pub ballot_index: LookupMap<(u32, Vec<u8>), u32>,
+
+    /// Proposals (next id, last id) which are not yet converted to the current layout. Set by
+    /// `migrate` and consumed by `migrate_proposals`.
+    pub migration: Option<(u32, u32)>,
+}
+
+fn assert_consents(simple_consent: &Consent, super_consent: &Consent) {
+    require!(
+        simple_consent.verify_threshold() && super_consent.verify_threshold(),
+        "threshold must be a percentage (0-100%)"
+    );
+    require!(
+        simple_consent.verify_quorum_percent() && super_consent.verify_quorum_percent(),
+        "quorum_percent must be a percentage (0-100%)"
+    );
+}
+
+/// Initial roles: the admin holds all roles until they are revoked by a `RevokeRole` proposal.
+fn admin_roles(admin: &AccountId) -> HashMap<AccountId, Vec<Role>> {
+    HashMap::from([(
+        admin.clone(),
+        vec![Role::ConsentUpdater, Role::WhitelistManager],
+    )])
 }
 
 #[near_bindgen]
@@ -79,10 +148,8 @@ impl Contract {
         simple_consent: Consent,
         super_consent: Consent,
     ) -> Self {
-        require!(
-            simple_consent.verify() && super_consent.verify(),
-            "threshold must be a percentage (0-100%)"
-        );
+        assert_consents(&simple_consent, &super_consent);
+        let roles = admin_roles(&accounts.admin);
         Self {
             prop_counter: 0,
             pre_vote_proposals: LookupMap::new(StorageKey::PreVoteProposals),
@@ -96,7 +163,23 @@ impl Contract {
             accounts: LazyOption::new(StorageKey::Accounts, Some(&accounts)),
             simple_consent,
             super_consent,
-            iom_whitelist: LookupSet::new(StorageKey::IomWhitelist),
+            whitelist: UnorderedMap::new(StorageKey::Whitelist),
+            withdraw_fee: WITHDRAW_FEE,
+            population: None,
+            delegations: LookupMap::new(StorageKey::Delegations),
+            delegators: LookupMap::new(StorageKey::Delegators),
+            delegates: LookupSet::new(StorageKey::Delegates),
+            ballots: LookupMap::new(StorageKey::Ballots),
+            ballot_index: LookupMap::new(StorageKey::BallotIndex),
+            voters: LookupMap::new(StorageKey::Voters),
+            account_votes: LookupMap::new(StorageKey::AccountVotes),
+            account_votes_len: LookupMap::new(StorageKey::AccountVotesLen),
+            vote_index: LookupMap::new(StorageKey::VoteIndex),
+            reveal_duration: REVEAL_DURATION,
+            commitments: LookupMap::new(StorageKey::Commitments),
+            constitutions: Vector::new(StorageKey::Constitutions),
+            roles: LazyOption::new(StorageKey::Roles, Some(&roles)),
+            migration: None,
         }
     }
 
@@ -109,7 +192,7 @@ impl Contract {
      **********/
 
     #[payable]
-    pub fn create_proposal_whitelist(&mut self, payload: CreatePropPayload) -> u32 {
+    pub fn create_proposal_whitelist(&mut self, payload: CreatePropPayload) -> PromiseOrValue<u32> {
         let caller = env::predecessor_account_id();
         self.assert_whitelist(&caller);
 
@@ -120,6 +203,8 @@ impl Contract {
     }
 
     /// Must be called via `iah_registry.is_human_call`.
+    /// `Veto`, `ApproveBudget` and `Dismiss` proposals are verified in the congress before they
+    /// are created, see `on_create_proposal`.
     #[payable]
     #[handle_result]
     pub fn create_proposal(
@@ -127,7 +212,7 @@ impl Contract {
         caller: AccountId,
         #[allow(unused_variables)] iah_proof: SBTs,
         payload: CreatePropPayload,
-    ) -> Result<u32, CreatePropError> {
+    ) -> Result<PromiseOrValue<u32>, CreatePropError> {
         if env::predecessor_account_id() != self.accounts.get().unwrap().iah_registry {
             return Err(CreatePropError::NotIAHreg);
         }
@@ -152,6 +237,38 @@ impl Contract {
         Ok(())
     }
 
+    /// Allows the proposer to withdraw a proposal from the pre-vote queue (before it is
+    /// overdue) or from the active queue before any vote is cast. The proposal is removed and
+    /// the bond is returned minus the `withdraw_fee`, which is sent to the community treasury.
+    /// Additional bond (top up) is fully returned.
+    /// Emits: proposal-withdraw.
+    #[handle_result]
+    pub fn withdraw_proposal(&mut self, id: u32) -> Result<(), WithdrawError> {
+        let caller = env::predecessor_account_id();
+        let mut p = if let Some(p) = self.pre_vote_proposals.get(&id) {
+            if env::block_timestamp_ms() - p.start > self.pre_vote_duration {
+                return Err(WithdrawError::Overdue);
+            }
+            p
+        } else {
+            let p = self.proposals.get(&id).ok_or(WithdrawError::NotFound)?;
+            if p.status != ProposalStatus::InProgress || p.has_votes() {
+                return Err(WithdrawError::AlreadyVoted);
+            }
+            p
+        };
+        if p.proposer != caller {
+            return Err(WithdrawError::NotProposer);
+        }
+
+        self.pre_vote_proposals.remove(&id);
+        self.proposals.remove(&id);
+        let treasury = self.accounts.get().unwrap().community_treasury;
+        let (refund, fee) = p.withdraw_bond(self.withdraw_fee, treasury);
+        emit_prop_withdraw(id, refund, fee);
+        Ok(())
+    }
+
     #[payable]
     #[handle_result]
     /// Allows to add more bond to a proposal to move it to the active queue. Anyone can top up.
@@ -226,6 +343,34 @@ impl Contract {
         self.support_proposal_impl(caller, locked_until, payload)
     }
 
+    pub fn unsupport_proposal_whitelist(&mut self, payload: u32) -> bool {
+        let caller = env::predecessor_account_id();
+        self.assert_whitelist(&caller);
+
+        match self.unsupport_proposal_impl(caller, payload) {
+            Ok(unsupported) => unsupported,
+            Err(err) => err.panic(),
+        }
+    }
+
+    /// Withdraws the caller support from a proposal in the pre-vote queue.
+    /// Returns false if the proposal is overdue (the proposal is slashed).
+    /// Must be called via `iah_registry.is_human_call`.
+    /// `payload` must be a pre-vote proposal ID.
+    /// Emits: proposal-unsupport.
+    #[handle_result]
+    pub fn unsupport_proposal(
+        &mut self,
+        caller: AccountId,
+        #[allow(unused_variables)] iah_proof: SBTs,
+        payload: u32,
+    ) -> Result<bool, PrevoteError> {
+        if env::predecessor_account_id() != self.accounts.get().unwrap().iah_registry {
+            return Err(PrevoteError::NotIAHreg);
+        }
+        self.unsupport_proposal_impl(caller, payload)
+    }
+
     /// Congressional support for a pre-vote proposal to move it to the active queue.
     /// Returns false if the proposal can't be supported because it is overdue.
     #[handle_result]
@@ -234,8 +379,7 @@ impl Contract {
         prop_id: u32,
         dao: AccountId,
     ) -> Result<Promise, PrevoteError> {
-        let a = self.accounts.get().unwrap();
-        if !(a.congress_coa == dao || a.congress_hom == dao || a.congress_tc == dao) {
+        if !self.accounts.get().unwrap().is_congress(&dao) {
             return Err(PrevoteError::NotCongress);
         }
 
@@ -297,6 +441,202 @@ impl Contract {
         self.vote_impl(caller, locked_until, payload)
     }
 
+    /// Removes vote records (and not revealed commitments) of a proposal with a finished voting,
+    /// together with their `get_votes` and `votes_by_account` index entries, and refunds the
+    /// storage deposit to the voter (delegated votes are paid from the voter delegation
+    /// deposit).
+    /// The proposal keeps the vote tally and the `votes_hash_chain`. The hash chain can be
+    /// verified with the `vote` events, which record the voters and their votes.
+    /// If `voters` is not set, then the caller vote is removed. Otherwise anyone can remove
+    /// votes of the listed accounts (in batches), accounts without a vote are skipped.
+    /// Returns the number of removed votes.
+    #[handle_result]
+    pub fn reclaim_vote_storage(
+        &mut self,
+        prop_id: u32,
+        voters: Option<Vec<AccountId>>,
+    ) -> Result<u32, ReclaimError> {
+        if let Some(p) = self.proposals.get(&prop_id) {
+            if p.is_active(self.vote_duration) {
+                return Err(ReclaimError::NotFinalized);
+            }
+        } else if self.pre_vote_proposals.contains_key(&prop_id) {
+            return Err(ReclaimError::NotFinalized);
+        } else if prop_id == 0 || prop_id > self.prop_counter {
+            return Err(ReclaimError::PropNotFound);
+        }
+        // otherwise the proposal was removed (spam or withdrawn)
+
+        let voters = voters.unwrap_or_else(|| vec![env::predecessor_account_id()]);
+        let mut refunds: HashMap<AccountId, Balance> = HashMap::new();
+        let mut removed = 0;
+        for voter in voters {
+            let storage_start = env::storage_usage();
+            if self.commitments.remove(&(prop_id, voter.clone())).is_some() {
+                let amount =
+                    (storage_start - env::storage_usage()) as Balance * env::storage_byte_cost();
+                *refunds.entry(voter.clone()).or_default() += amount;
+                removed += 1;
+                continue;
+            }
+            if self.votes.remove(&(prop_id, voter.clone())).is_some() {
+                self.remove_vote_index(prop_id, &voter);
+                let amount =
+                    (storage_start - env::storage_usage()) as Balance * env::storage_byte_cost();
+                *refunds.entry(voter).or_default() += amount;
+                removed += 1;
+            }
+        }
+        for (account, amount) in refunds {
+            Promise::new(account).transfer(amount);
+        }
+        Ok(removed)
+    }
+
+    #[payable]
+    pub fn commit_vote_whitelist(&mut self, payload: CommitPayload) {
+        let caller = env::predecessor_account_id();
+        self.assert_whitelist(&caller);
+
+        if let Err(err) = self.commit_vote_impl(
+            caller,
+            env::block_timestamp_ms() + MAX_DURATION + 1,
+            payload,
+        ) {
+            err.panic();
+        }
+    }
+
+    /// Commits a vote for a secret ballot proposal during the voting. The vote must be
+    /// revealed with `reveal_vote` in the reveal window (after the voting end and before
+    /// `voting end + proposal.reveal_duration`), otherwise it only counts as abstain toward
+    /// the quorum. Committing again overwrites the previous commitment.
+    /// `payload.commitment` must be `sha256(borsh(voter, vote) || salt)`.
+    /// Must be called via `iah_registry.is_human_call_lock` with
+    /// `lock_duration: self.vote_duration + 1`.
+    /// Emits: vote-commit.
+    #[payable]
+    #[handle_result]
+    pub fn commit_vote(
+        &mut self,
+        caller: AccountId,
+        locked_until: u64,
+        #[allow(unused_variables)] iah_proof: Option<SBTs>,
+        payload: CommitPayload,
+    ) -> Result<(), VoteError> {
+        if env::predecessor_account_id() != self.accounts.get().unwrap().iah_registry {
+            return Err(VoteError::NotIAHreg);
+        }
+        self.commit_vote_impl(caller, locked_until, payload)
+    }
+
+    /// Reveals the caller vote committed with `commit_vote`. The vote is recorded (as a
+    /// direct vote) if it matches the commitment.
+    /// Caller has to attach deposit to cover the vote storage.
+    /// Emits: vote.
+    #[payable]
+    #[handle_result]
+    pub fn reveal_vote(
+        &mut self,
+        prop_id: u32,
+        vote: Vote,
+        salt: Base64VecU8,
+    ) -> Result<(), VoteError> {
+        self.reveal_vote_impl(env::predecessor_account_id(), prop_id, vote, salt.0)
+    }
+
+    /// Allows admin to set the reveal window of new secret ballot proposals.
+    pub fn admin_set_reveal_duration(&mut self, reveal_duration: u64) {
+        self.assert_admin();
+        require!(
+            (MIN_DURATION..=MAX_DURATION).contains(&reveal_duration),
+            "reveal duration must be between 1 and 90 days"
+        );
+        self.reveal_duration = reveal_duration;
+    }
+
+    #[payable]
+    pub fn delegate_whitelist(&mut self, payload: AccountId) {
+        let caller = env::predecessor_account_id();
+        self.assert_whitelist(&caller);
+
+        if let Err(err) = self.delegate_impl(caller, u64::MAX, payload) {
+            err.panic();
+        }
+    }
+
+    /// Delegates caller votes to the `payload` account, which must accept delegations (see
+    /// `accept_delegations`). When the delegate votes, the vote is also recorded for each
+    /// delegator who didn't vote directly. Direct vote of the delegator always overwrites the
+    /// delegated vote.
+    /// Delegated votes are counted only for proposals which voting ends before `locked_until`.
+    /// Caller has to attach deposit to cover the delegation storage. The rest of the deposit
+    /// pays the storage of the votes recorded by the delegate (`VOTE_STORAGE` per vote). A
+    /// delegated vote is not recorded once the deposit is used up. The remaining deposit is
+    /// returned when the delegation is removed.
+    /// Must be called via `iah_registry.is_human_call_lock`.
+    /// Emits `delegate` event (and `undelegate` if the caller already delegated to someone else).
+    #[payable]
+    #[handle_result]
+    pub fn delegate(
+        &mut self,
+        caller: AccountId,
+        locked_until: u64,
+        #[allow(unused_variables)] iah_proof: Option<SBTs>,
+        payload: AccountId,
+    ) -> Result<(), DelegateError> {
+        if env::predecessor_account_id() != self.accounts.get().unwrap().iah_registry {
+            return Err(DelegateError::NotIAHreg);
+        }
+        self.delegate_impl(caller, locked_until, payload)
+    }
+
+    /// Revokes the caller delegation. Votes, which were already cast by the delegate, are not
+    /// reverted, but the caller can overwrite them by voting directly.
+    /// Emits `undelegate` event.
+    #[handle_result]
+    pub fn undelegate(&mut self) -> Result<(), DelegateError> {
+        let caller = env::predecessor_account_id();
+        match self.remove_delegation(&caller) {
+            Some(_) => Ok(()),
+            None => Err(DelegateError::NotDelegated),
+        }
+    }
+
+    /// Starts (`accept == true`) or stops accepting new delegations of votes to the caller.
+    /// Existing delegations are not removed, see `remove_delegator`.
+    /// Caller has to attach deposit to cover the storage when starting to accept delegations.
+    #[payable]
+    #[handle_result]
+    pub fn accept_delegations(&mut self, accept: bool) -> Result<(), DelegateError> {
+        let caller = env::predecessor_account_id();
+        let storage_start = env::storage_usage();
+        if accept {
+            self.delegates.insert(&caller);
+        } else {
+            self.delegates.remove(&caller);
+        }
+        let storage_start = std::cmp::min(storage_start, env::storage_usage());
+        if let Err(reason) = finalize_storage_check(storage_start, 0, caller) {
+            return Err(DelegateError::Storage(reason));
+        }
+        Ok(())
+    }
+
+    /// Allows the delegate to remove the `delegator` delegation. Votes, which were already
+    /// cast by the delegate, are not reverted.
+    /// Emits `undelegate` event.
+    #[handle_result]
+    pub fn remove_delegator(&mut self, delegator: AccountId) -> Result<(), DelegateError> {
+        match self.delegations.get(&delegator) {
+            Some(d) if d.delegate == env::predecessor_account_id() => {
+                self.remove_delegation(&delegator);
+                Ok(())
+            }
+            _ => Err(DelegateError::NotDelegated),
+        }
+    }
+
     /// Allows anyone to execute or slash the proposal.
     /// If proposal is slasheable, the user who executes gets REMOVE_REWARD.
     #[handle_result]
@@ -311,7 +651,7 @@ impl Contract {
             return Err(ExecError::AlreadyFinalized);
         }
 
-        prop.recompute_status(self.vote_duration, self.prop_consent(&prop));
+        self.recompute_status(id, &mut prop);
         match prop.status {
             ProposalStatus::PreVote => panic_str("pre-vote proposal can't be in the active queue"),
             ProposalStatus::InProgress => return Err(ExecError::InProgress),
@@ -329,6 +669,11 @@ impl Contract {
             }
             ProposalStatus::Approved | ProposalStatus::Failed => (), // execute below
         };
+        if let PropKind::RatifyConstitution { version, .. } = &prop.kind {
+            if *version <= self.constitution_version() {
+                return Err(ExecError::StaleConstitution);
+            }
+        }
 
         prop.refund_bond();
         prop.status = ProposalStatus::Executed;
@@ -346,7 +691,12 @@ impl Contract {
             PropKind::Veto { dao, prop_id } => {
                 out = ext_congress::ext(dao.clone()).veto_hook(*prop_id).into();
             }
-            PropKind::Text | PropKind::TextSuper | PropKind::ApproveBudget { .. } => (),
+            PropKind::ApproveBudget { dao, prop_id } => {
+                out = ext_congress::ext(dao.clone())
+                    .ratify_budget_hook(*prop_id)
+                    .into();
+            }
+            PropKind::Text | PropKind::TextSuper | PropKind::MultipleChoice { .. } => (),
             PropKind::FunctionCall {
                 receiver_id,
                 actions,
@@ -376,6 +726,52 @@ impl Contract {
                 self.pre_vote_duration = *pre_vote_duration;
                 self.vote_duration = *vote_duration;
             }
+            PropKind::UpdateConsent {
+                simple_consent,
+                super_consent,
+            } => {
+                self.simple_consent = *simple_consent;
+                self.super_consent = *super_consent;
+            }
+            PropKind::UpdatePreVoteSupport { pre_vote_support } => {
+                self.pre_vote_support = *pre_vote_support;
+            }
+            PropKind::GrantRole { account, role } => {
+                let mut roles = self.roles.get().unwrap();
+                let account_roles = roles.entry(account.clone()).or_default();
+                if !account_roles.contains(role) {
+                    account_roles.push(*role);
+                    self.roles.set(&roles);
+                    emit_role_grant(id, account, *role);
+                }
+            }
+            PropKind::RevokeRole { account, role } => {
+                let mut roles = self.roles.get().unwrap();
+                if let Some(account_roles) = roles.get_mut(account) {
+                    if account_roles.contains(role) {
+                        account_roles.retain(|r| r != role);
+                        if account_roles.is_empty() {
+                            roles.remove(account);
+                        }
+                        self.roles.set(&roles);
+                        emit_role_revoke(id, account, *role);
+                    }
+                }
+            }
+            PropKind::RatifyConstitution {
+                document_hash,
+                uri,
+                version,
+            } => {
+                self.constitutions.push(&Constitution {
+                    document_hash: document_hash.clone(),
+                    uri: uri.clone(),
+                    version: *version,
+                    prop_id: id,
+                    ratified_at: env::block_timestamp_ms(),
+                });
+                emit_constitution_ratify(id, *version, document_hash);
+            }
         };
 
         self.proposals.insert(&id, &prop);
@@ -396,27 +792,88 @@ impl Contract {
         Ok(out)
     }
 
+    /// Fetches the IAH population (supply of the human SBT class) from the registry. The
+    /// population is used to compute quorum of proposals entering the active queue.
+    /// NOTE: `sbt_supply_by_class` counts the SBTs of the class, not unique humans. The
+    /// supply includes tokens which are not burned yet (eg: expired ones), so the population is
+    /// an approximation (upper bound) of the number of verified humans.
+    /// Anyone can call.
+    /// Emits `population-update` event.
+    pub fn update_population(&mut self) -> Promise {
+        let p = match &self.population {
+            Some(p) => p,
+            None => panic_str("population class is not set"),
+        };
+        ext_registry::ext(self.accounts.get().unwrap().iah_registry)
+            .sbt_supply_by_class(p.issuer.clone(), p.class)
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(POPULATION_CALLBACK_GAS)
+                    .on_population_update(),
+            )
+    }
+
     /*****************
      * ADMIN
      ****************/
 
-    /// Allows admin to udpate the consent based on the latest amount of humans verified accounts.
+    /// Allows a `ConsentUpdater` to udpate the consent based on the latest amount of
+    /// humans verified accounts.
     pub fn admin_update_consent(&mut self, simple_consent: Consent, super_consent: Consent) {
-        self.assert_admin();
+        self.assert_role(Role::ConsentUpdater);
+        assert_consents(&simple_consent, &super_consent);
         self.simple_consent = simple_consent;
         self.super_consent = super_consent;
     }
 
-    /// Allows admin to add a user to the whitelist.
-    pub fn admin_add_to_whitelist(&mut self, user: AccountId) {
+    /// Allows admin to set the SBT class (`issuer`, `class`) which is counted as the IAH
+    /// population. The population must be fetched again with `update_population`.
+    pub fn admin_set_population_class(&mut self, issuer: AccountId, class: u64) {
         self.assert_admin();
-        self.iom_whitelist.insert(user);
+        self.population = Some(Population {
+            issuer,
+            class,
+            count: 0,
+            updated_at: 0,
+        });
     }
 
-    /// Allows admin to remove a user from the whitelist.
-    pub fn admin_remove_from_whitelist(&mut self, user: AccountId) {
+    /// Allows admin to set the fee charged when the proposer withdraws a proposal.
+    pub fn admin_set_withdraw_fee(&mut self, fee: U128) {
         self.assert_admin();
-        self.iom_whitelist.remove(&user);
+        self.withdraw_fee = fee.0;
+    }
+
+    /// Allows a `WhitelistManager` to add a user to the whitelist until `expires_at`
+    /// (unix time in milliseconds). `reason` is a hash of the reason / evidence. Overwrites the
+    /// existing user entry.
+    /// Emits: whitelist-add.
+    pub fn admin_add_to_whitelist(
+        &mut self,
+        user: AccountId,
+        expires_at: u64,
+        reason: Base64VecU8,
+    ) {
+        self.assert_role(Role::WhitelistManager);
+        let now = env::block_timestamp_ms();
+        require!(expires_at > now, "expires_at must be in the future");
+        require!(!reason.0.is_empty(), "reason must not be empty");
+        let entry = WhitelistEntry {
+            expires_at,
+            reason,
+            authority: env::predecessor_account_id(),
+            added_at: now,
+        };
+        emit_whitelist_add(&user, &entry);
+        self.whitelist.insert(&user, &entry);
+    }
+
+    /// Allows a `WhitelistManager` to remove a user from the whitelist.
+    pub fn admin_remove_from_whitelist(&mut self, user: AccountId) {
+        self.assert_role(Role::WhitelistManager);
+        if self.whitelist.remove(&user).is_some() {
+            emit_whitelist_remove(&user, &env::predecessor_account_id());
+        }
     }
 
     // /// udpate voting time for e2e tests purposes
@@ -456,6 +913,72 @@ impl Contract {
         };
     }
 
+    /// Creates a proposal verified in the congress or created with a bond below
+    /// `active_queue_bond`. Promise results are: the congress verification (only for proposals
+    /// verified in the congress), followed by the `is_member` responses of the congress houses
+    /// (only when the bond is below `active_queue_bond`). Proposals of congress members go
+    /// directly to the active queue.
+    /// Returns the new proposal ID, or `None` when the verification failed (the target proposal
+    /// doesn't exist or can't be vetoed or approved, or the member is not part of the house),
+    /// or when the bond doesn't cover the proposal storage. In such case the bond is returned.
+    #[private]
+    pub fn on_create_proposal(
+        &mut self,
+        caller: AccountId,
+        bond: U128,
+        payload: CreatePropPayload,
+    ) -> Option<u32> {
+        let results = env::promise_results_count();
+        let verified = impls::is_verified_in_congress(&payload.kind);
+        let members_start = u64::from(verified);
+        require!(results > 0, "ERR_UNEXPECTED_CALLBACK_PROMISES");
+        if verified {
+            let valid = match env::promise_result(0) {
+                PromiseResult::Successful(resp) => {
+                    impls::verify_congress_response(&payload.kind, &resp)
+                }
+                _ => false,
+            };
+            if !valid {
+                Promise::new(caller).transfer(bond.0);
+                return None;
+            }
+        }
+        let congress_member =
+            (members_start..results).any(|i| impls::is_member_response(env::promise_result(i)));
+
+        let storage_start = env::storage_usage();
+        let id = self.insert_proposal(caller.clone(), bond.0, payload, congress_member);
+        // storage is paid from the bond. If the bond doesn't cover it, the proposal is not
+        // created and the bond is returned.
+        let required = (env::storage_usage() - storage_start) as u128 * env::storage_byte_cost();
+        if required > bond.0 {
+            self.remove_new_proposal(id);
+            Promise::new(caller).transfer(bond.0);
+            return None;
+        }
+        self.finalize_proposal(id, required);
+        Some(id)
+    }
+
+    #[private]
+    pub fn on_population_update(
+        &mut self,
+        #[callback_result] supply: Result<u64, near_sdk::PromiseError>,
+    ) {
+        let count = match supply {
+            Ok(count) => count,
+            Err(_) => panic_str("can't fetch the population from the registry"),
+        };
+        let p = self
+            .population
+            .as_mut()
+            .expect("population class is not set");
+        p.count = count;
+        p.updated_at = env::block_timestamp_ms();
+        emit_population_update(count);
+    }
+
     /*****************
      * INTERNAL
      ****************/
@@ -467,8 +990,21 @@ impl Contract {
         );
     }
 
+    /// Panics if the caller doesn't have the `role`.
+    fn assert_role(&self, role: Role) {
+        require!(
+            self.has_role(&env::predecessor_account_id(), role),
+            "not authorized"
+        );
+    }
+
     fn assert_whitelist(&self, account_id: &AccountId) {
-        require!(self.iom_whitelist.contains(account_id), "not whitelisted");
+        let e = self.whitelist.get(account_id);
+        require!(e.is_some(), "not whitelisted");
+        require!(
+            e.unwrap().expires_at > env::block_timestamp_ms(),
+            "whitelist entry expired"
+        );
     }
 
     fn remove_pre_vote_prop(&mut self, id: u32) -> Result<Proposal, PrevoteError> {
@@ -487,6 +1023,7 @@ impl Contract {
         p.supported.clear();
         p.status = ProposalStatus::InProgress;
         p.start = env::block_timestamp_ms();
+        p.quorum = Some(self.active_quorum(p));
         self.proposals.insert(&prop_id, p);
         emit_prop_active(prop_id);
     }
@@ -499,29 +1036,146 @@ impl Contract {
 
     fn prop_consent(&self, prop: &Proposal) -> Consent {
         match prop.kind.required_consent() {
-            ConsentKind::Simple => self.simple_consent.clone(),
-            ConsentKind::Super => self.super_consent.clone(),
+            ConsentKind::Simple => self.simple_consent,
+            ConsentKind::Super => self.super_consent,
+        }
+    }
+
+    /// Version of the current constitution, 0 if no constitution was ratified yet.
+    fn constitution_version(&self) -> u32 {
+        let len = self.constitutions.len();
+        if len == 0 {
+            return 0;
         }
+        self.constitutions.get(len - 1).unwrap().version
     }
 
-    fn add_vote(&mut self, prop_id: u32, user: AccountId, vote: Vote, prop: &mut Proposal) {
-        match vote {
+    /// Quorum for a proposal entering the active queue, based on the latest IAH population.
+    fn active_quorum(&self, prop: &Proposal) -> u32 {
+        let population = self.population.as_ref().map_or(0, |p| p.count);
+        self.prop_consent(prop).quorum_for(population)
+    }
+
+    /// Removes the `delegator` delegation and returns the remaining deposit to the delegator.
+    /// Returns the removed delegation.
+    fn remove_delegation(&mut self, delegator: &AccountId) -> Option<Delegation> {
+        let d = self.delegations.remove(delegator)?;
+        if d.deposit.0 > 0 {
+            Promise::new(delegator.clone()).transfer(d.deposit.0);
+        }
+        let mut delegators = self.delegators.get(&d.delegate).unwrap_or_default();
+        delegators.retain(|a| a != delegator);
+        if delegators.is_empty() {
+            self.delegators.remove(&d.delegate);
+        } else {
+            self.delegators.insert(&d.delegate, &delegators);
+        }
+        emit_undelegate(delegator, &d.delegate);
+        Some(d)
+    }
+
+    /// Recomputes the proposal status (see `Proposal::recompute_status`).
+    fn recompute_status(&self, prop_id: u32, prop: &mut Proposal) {
+        prop.recompute_status(self.vote_duration, self.prop_consent(prop), |p| {
+            self.choice_result(prop_id, p)
+        });
+    }
+
+    /// Tallies the multiple choice proposal ballots. Returns `None` if the proposal is not a
+    /// multiple choice proposal.
+    fn choice_result(&self, prop_id: u32, prop: &Proposal) -> Option<ChoiceResult> {
+        let ballots: Vec<(Vec<u8>, u32)> = (0..prop.ballots)
+            .filter_map(|i| self.ballots.get(&(prop_id, i)))
+            .collect();
+        prop.choice_result(&ballots)
+    }
+
+    /// Records (`add == true`) or removes a multiple choice ballot. New rankings are appended
+    /// to the proposal ballots index.
+    fn update_ballots(&mut self, prop_id: u32, prop: &mut Proposal, ranking: &[u8], add: bool) {
+        let key = (prop_id, ranking.to_vec());
+        let (idx, mut votes) = match self.ballot_index.get(&key) {
+            Some(idx) => (idx, self.ballots.get(&(prop_id, idx)).unwrap().1),
+            None if add => {
+                self.ballot_index.insert(&key, &prop.ballots);
+                prop.ballots += 1;
+                (prop.ballots - 1, 0)
+            }
+            None => return,
+        };
+        if add {
+            votes += 1;
+        } else {
+            votes -= 1;
+        }
+        self.ballots.insert(&(prop_id, idx), &(key.1, votes));
+    }
+
+    /// Removes the vote from the `voters` and `account_votes` indexes. Trailing empty entries
+    /// of the account votes index are dropped from its length.
+    fn remove_vote_index(&mut self, prop_id: u32, voter: &AccountId) {
+        let (voter_idx, account_idx) = match self.vote_index.remove(&(prop_id, voter.clone())) {
+            Some(idx) => idx,
+            None => return,
+        };
+        self.voters.remove(&(prop_id, voter_idx));
+        self.account_votes.remove(&(voter.clone(), account_idx));
+        let mut len = self.account_votes_len.get(voter).unwrap_or(0);
+        while len > 0 && !self.account_votes.contains_key(&(voter.clone(), len - 1)) {
+            len -= 1;
+        }
+        if len == 0 {
+            self.account_votes_len.remove(voter);
+        } else {
+            self.account_votes_len.insert(voter, &len);
+        }
+    }
+
+    fn add_vote(
+        &mut self,
+        prop_id: u32,
+        user: AccountId,
+        vote: Vote,
+        delegate: Option<AccountId>,
+        prop: &mut Proposal,
+    ) {
+        match &vote {
             Vote::Abstain => prop.abstain += 1,
             Vote::Approve => prop.approve += 1,
             Vote::Reject => prop.reject += 1,
             Vote::Spam => prop.spam += 1,
+            Vote::Rank(ranking) => {
+                prop.approve += 1;
+                self.update_ballots(prop_id, prop, ranking, true);
+            }
         };
+        prop.add_to_hash_chain(&user, &vote);
         // allow to overwrite existing votes
         let v = VoteRecord {
             timestamp: env::block_timestamp_ms(),
             vote,
+            delegate,
         };
-        if let Some(old_vote) = self.votes.insert(&(prop_id, user), &v) {
-            match old_vote.vote {
+        match self.votes.insert(&(prop_id, user.clone()), &v) {
+            Some(old_vote) => match old_vote.vote {
                 Vote::Approve => prop.approve -= 1,
                 Vote::Reject => prop.reject -= 1,
                 Vote::Abstain => prop.abstain -= 1,
                 Vote::Spam => prop.spam -= 1,
+                Vote::Rank(ranking) => {
+                    prop.approve -= 1;
+                    self.update_ballots(prop_id, prop, &ranking, false);
+                }
+            },
+            // new voter: update the indexes
+            None => {
+                self.voters.insert(&(prop_id, prop.voters), &user);
+                let len = self.account_votes_len.get(&user).unwrap_or(0);
+                self.account_votes.insert(&(user.clone(), len), &prop_id);
+                self.account_votes_len.insert(&user, &(len + 1));
+                self.vote_index
+                    .insert(&(prop_id, user.clone()), &(prop.voters, len));
+                prop.voters += 1;
             }
         }
     }
@@ -531,7 +1185,11 @@ impl Contract {
 mod unit_tests {
     use near_sdk::{test_utils::VMContextBuilder, testing_env, AccountId, VMContext, ONE_NEAR};
 
-    use crate::{view::ConfigOutput, *};
+    use crate::{
+        storage::Population,
+        view::{ConfigOutput, WhitelistOutput},
+        *,
+    };
 
     /// 1ms in nano seconds
     const MSECOND: u64 = 1_000_000;
@@ -543,7 +1201,8 @@ mod unit_tests {
     const PRE_BOND: u128 = ONE_NEAR * 3;
     const BOND: u128 = ONE_NEAR * 500;
     const PRE_VOTE_SUPPORT: u32 = 10;
-    const VOTE_DEPOSIT: u128 = ONE_NEAR / 1000;
+    /// storage byte cost is 10^19 yoctoNEAR
+    const VOTE_DEPOSIT: u128 = VOTE_STORAGE as u128 * ONE_NEAR / 100_000;
 
     fn acc(idx: u8) -> AccountId {
         AccountId::new_unchecked(format!("user-{}.near", idx))
@@ -581,11 +1240,24 @@ mod unit_tests {
         VoteRecord {
             timestamp: timestamp_ns / MSECOND,
             vote,
+            delegate: None,
         }
     }
 
     fn create_prop_payload(kind: PropKind, description: String) -> CreatePropPayload {
-        CreatePropPayload { kind, description }
+        CreatePropPayload {
+            kind,
+            description,
+            secret_ballot: false,
+        }
+    }
+
+    fn prop_id(res: Result<PromiseOrValue<u32>, CreatePropError>) -> u32 {
+        match res {
+            Ok(PromiseOrValue::Value(id)) => id,
+            Ok(PromiseOrValue::Promise(_)) => panic!("expected proposal id, got promise"),
+            Err(err) => panic!("expected proposal id, got: {:?}", err),
+        }
     }
 
     fn iah_proof() -> SBTs {
@@ -612,10 +1284,12 @@ mod unit_tests {
             Consent {
                 quorum: 3,
                 threshold: 50,
+                quorum_percent: None,
             },
             Consent {
                 quorum: 5,
                 threshold: 60,
+                quorum_percent: None,
             },
         );
         context.block_timestamp = START;
@@ -624,13 +1298,12 @@ mod unit_tests {
         context.account_balance = ONE_NEAR * 2000;
         testing_env!(context.clone());
 
-        let id = contract
-            .create_proposal(
-                acc(1),
-                iah_proof(),
-                create_prop_payload(PropKind::Text, "Proposal unit test 1".to_string()),
-            )
-            .unwrap();
+        let id = create_prop(
+            &context,
+            &mut contract,
+            create_prop_payload(PropKind::Text, "Proposal unit test 1".to_string()),
+            false,
+        );
 
         context.attached_deposit = 0;
         testing_env!(context.clone());
@@ -646,6 +1319,14 @@ mod unit_tests {
         ctx.block_timestamp / MSECOND + PRE_VOTE_DURATION + 1
     }
 
+    fn wl_expiry(ctx: &VMContext) -> u64 {
+        ctx.block_timestamp / MSECOND + VOTE_DURATION
+    }
+
+    fn wl_reason() -> Base64VecU8 {
+        vec![1; 32].into()
+    }
+
     fn vote(mut ctx: VMContext, ctr: &mut Contract, accs: Vec<AccountId>, id: u32, vote: Vote) {
         for a in accs {
             ctx.predecessor_account_id = iah_registry();
@@ -691,13 +1372,52 @@ mod unit_tests {
     fn create_proposal(mut ctx: VMContext, ctr: &mut Contract, bond: Balance) -> u32 {
         ctx.predecessor_account_id = iah_registry();
         ctx.attached_deposit = bond;
-        testing_env!(ctx.clone());
-        ctr.create_proposal(
-            acc(1),
-            iah_proof(),
+        create_prop(
+            &ctx,
+            ctr,
             create_prop_payload(PropKind::Text, "Proposal unit test".to_string()),
+            false,
         )
-        .unwrap()
+    }
+
+    /// Creates a proposal by acc(1) with the `ctx` attached deposit. When the congress is
+    /// queried (bond below `active_queue_bond`), the `on_create_proposal` callback is called
+    /// with the congress houses `is_member` responses.
+    fn create_prop(
+        ctx: &VMContext,
+        ctr: &mut Contract,
+        payload: CreatePropPayload,
+        congress_member: bool,
+    ) -> u32 {
+        let mut ctx = ctx.clone();
+        ctx.predecessor_account_id = iah_registry();
+        testing_env!(ctx.clone());
+        let id = match ctr.create_proposal(acc(1), iah_proof(), payload.clone()) {
+            Ok(PromiseOrValue::Value(id)) => id,
+            Ok(PromiseOrValue::Promise(_)) => {
+                let mut ctx = ctx.clone();
+                let bond = ctx.attached_deposit;
+                ctx.predecessor_account_id = ctx.current_account_id.clone();
+                ctx.attached_deposit = 0;
+                // is_member responses of HoM, CoA and TC
+                testing_env!(
+                    ctx.clone(),
+                    near_sdk::VMConfig::test(),
+                    near_sdk::RuntimeFeesConfig::test(),
+                    Default::default(),
+                    vec![
+                        json_resp(serde_json::json!(false)),
+                        json_resp(serde_json::json!(congress_member)),
+                        json_resp(serde_json::json!(false)),
+                    ],
+                );
+                ctr.on_create_proposal(acc(1), U128(bond), payload)
+                    .expect("proposal must be created")
+            }
+            Err(err) => panic!("expected proposal id, got: {:?}", err),
+        };
+        testing_env!(ctx);
+        id
     }
 
     fn create_proposal_with_status(
@@ -708,13 +1428,11 @@ mod unit_tests {
         ctx.predecessor_account_id = iah_registry();
         ctx.attached_deposit = BOND;
         testing_env!(ctx.clone());
-        let id = ctr
-            .create_proposal(
-                acc(1),
-                iah_proof(),
-                create_prop_payload(PropKind::Text, "Proposal unit test".to_string()),
-            )
-            .unwrap();
+        let id = prop_id(ctr.create_proposal(
+            acc(1),
+            iah_proof(),
+            create_prop_payload(PropKind::Text, "Proposal unit test".to_string()),
+        ));
         let mut prop = ctr.proposals.get(&id).unwrap();
         prop.status = status;
         ctr.proposals.insert(&id, &prop);
@@ -744,6 +1462,7 @@ mod unit_tests {
         prop1.proposal.status = ProposalStatus::InProgress;
         prop1.proposal.start = (START + MSECOND) / MSECOND;
         prop1.proposal.additional_bond = Some((acc(2), BOND - PRE_BOND));
+        prop1.proposal.quorum = Some(3);
         assert_eq!(ctr.get_proposals(0, 10, None), vec![prop1.clone()]);
 
         //
@@ -781,6 +1500,8 @@ mod unit_tests {
             Ok(())
         );
         prop1.proposal.spam += 1;
+        prop1.proposal.voters += 1;
+        prop1.proposal.add_to_hash_chain(&acc(5), &Vote::Spam);
         insert_vote(&mut ctr, id, acc(5), ctx.block_timestamp, Vote::Spam);
         assert!(matches!(ctr.execute(id), Err(ExecError::InProgress)));
 
@@ -792,7 +1513,7 @@ mod unit_tests {
             iah_proof(),
             create_prop_payload(PropKind::Text, "proposal".to_owned()),
         );
-        assert_eq!(resp, Err(CreatePropError::MinBond));
+        assert_eq!(resp.err(), Some(CreatePropError::MinBond));
 
         //
         // Create a new proposal with bond to active queue and check double vote and expire
@@ -815,6 +1536,10 @@ mod unit_tests {
 
         prop2.proposal.approve = 1;
         prop2.proposal.reject = 2;
+        prop2.proposal.voters = 3;
+        prop2.proposal.add_to_hash_chain(&acc(3), &Vote::Approve);
+        prop2.proposal.add_to_hash_chain(&acc(1), &Vote::Reject);
+        prop2.proposal.add_to_hash_chain(&acc(2), &Vote::Reject);
         insert_vote(&mut ctr, id, acc(3), ctx.block_timestamp, Vote::Approve);
         insert_vote(&mut ctr, id, acc(1), ctx.block_timestamp, Vote::Reject);
         insert_vote(&mut ctr, id, acc(2), ctx.block_timestamp, Vote::Reject);
@@ -1015,19 +1740,18 @@ mod unit_tests {
         let (mut ctx, mut ctr, _) = setup_ctr(PRE_BOND);
         ctx.attached_deposit = BOND;
         testing_env!(ctx.clone());
-        let id = ctr
-            .create_proposal(
-                acc(1),
-                iah_proof(),
-                CreatePropPayload {
-                    kind: PropKind::UpdateBonds {
-                        pre_vote_bond: (PRE_BOND * 2).into(),
-                        active_queue_bond: (BOND * 5).into(),
-                    },
-                    description: "updating bonds".to_owned(),
+        let id = prop_id(ctr.create_proposal(
+            acc(1),
+            iah_proof(),
+            CreatePropPayload {
+                kind: PropKind::UpdateBonds {
+                    pre_vote_bond: (PRE_BOND * 2).into(),
+                    active_queue_bond: (BOND * 5).into(),
                 },
-            )
-            .unwrap();
+                description: "updating bonds".to_owned(),
+                secret_ballot: false,
+            },
+        ));
         vote(
             ctx.clone(),
             &mut ctr,
@@ -1056,19 +1780,18 @@ mod unit_tests {
         let (mut ctx, mut ctr, _) = setup_ctr(PRE_BOND);
         ctx.attached_deposit = BOND;
         testing_env!(ctx.clone());
-        let id = ctr
-            .create_proposal(
-                acc(1),
-                iah_proof(),
-                CreatePropPayload {
-                    kind: PropKind::UpdateVoteDuration {
-                        pre_vote_duration: MIN_DURATION,
-                        vote_duration: MAX_DURATION,
-                    },
-                    description: "updating voting duration".to_owned(),
+        let id = prop_id(ctr.create_proposal(
+            acc(1),
+            iah_proof(),
+            CreatePropPayload {
+                kind: PropKind::UpdateVoteDuration {
+                    pre_vote_duration: MIN_DURATION,
+                    vote_duration: MAX_DURATION,
                 },
-            )
-            .unwrap();
+                description: "updating voting duration".to_owned(),
+                secret_ballot: false,
+            },
+        ));
         vote(
             ctx.clone(),
             &mut ctr,
@@ -1115,15 +1838,13 @@ mod unit_tests {
         // Get refund for proposal with no status update
         ctx.attached_deposit = BOND;
         testing_env!(ctx.clone());
-        let id2 = ctr
-            .create_proposal(
-                acc(1),
-                iah_proof(),
-                create_prop_payload(PropKind::Text, "Proposal unit test".to_string()),
-            )
-            .unwrap();
-        p = ctr.get_proposal(id2).unwrap();
-
+        let id2 = prop_id(ctr.create_proposal(
+            acc(1),
+            iah_proof(),
+            create_prop_payload(PropKind::Text, "Proposal unit test".to_string()),
+        ));
+        p = ctr.get_proposal(id2).unwrap();
+
         // Set time after voting period
         ctx.block_timestamp = (p.proposal.start + ctr.vote_duration + 1) * MSECOND;
         testing_env!(ctx);
@@ -1143,10 +1864,12 @@ mod unit_tests {
             simple_consent: Consent {
                 quorum: 3,
                 threshold: 50,
+                quorum_percent: None,
             },
             super_consent: Consent {
                 quorum: 5,
                 threshold: 60,
+                quorum_percent: None,
             },
             pre_vote_duration: PRE_VOTE_DURATION,
             vote_duration: VOTE_DURATION,
@@ -1158,6 +1881,9 @@ mod unit_tests {
                 congress_tc: tc(),
                 admin: admin(),
             },
+            population: None,
+            withdraw_fee: U128(WITHDRAW_FEE),
+            reveal_duration: REVEAL_DURATION,
         };
         assert_eq!(ctr.config(), expected);
     }
@@ -1176,6 +1902,8 @@ mod unit_tests {
             Ok(())
         );
         p.proposal.approve = 1;
+        p.proposal.voters = 1;
+        p.proposal.add_to_hash_chain(&acc(1), &Vote::Approve);
         insert_vote(&mut ctr, id, acc(1), ctx.block_timestamp, Vote::Approve);
         assert_eq!(ctr.get_proposal(id).unwrap(), p);
 
@@ -1185,6 +1913,7 @@ mod unit_tests {
         );
         p.proposal.approve = 0;
         p.proposal.abstain = 1;
+        p.proposal.add_to_hash_chain(&acc(1), &Vote::Abstain);
         insert_vote(&mut ctr, id, acc(1), ctx.block_timestamp, Vote::Abstain);
         assert_eq!(ctr.get_proposal(id).unwrap(), p);
 
@@ -1194,6 +1923,7 @@ mod unit_tests {
         );
         p.proposal.abstain = 0;
         p.proposal.reject = 1;
+        p.proposal.add_to_hash_chain(&acc(1), &Vote::Reject);
         insert_vote(&mut ctr, id, acc(1), ctx.block_timestamp, Vote::Reject);
         assert_eq!(ctr.get_proposal(id).unwrap(), p);
 
@@ -1203,10 +1933,337 @@ mod unit_tests {
         );
         p.proposal.reject = 0;
         p.proposal.spam = 1;
+        p.proposal.add_to_hash_chain(&acc(1), &Vote::Spam);
         insert_vote(&mut ctr, id, acc(1), ctx.block_timestamp, Vote::Spam);
         assert_eq!(ctr.get_proposal(id).unwrap(), p);
     }
 
+    #[test]
+    fn reclaim_vote_storage() {
+        let (mut ctx, mut ctr, id) = setup_ctr(BOND);
+        let pre_vote_id = create_proposal(ctx.clone(), &mut ctr, PRE_BOND);
+        vote(
+            ctx.clone(),
+            &mut ctr,
+            vec![acc(1), acc(2), acc(3)],
+            id,
+            Vote::Approve,
+        );
+        // the vote event records the vote, the hash chain can be verified after the records removal
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"ndc-congress","version":"1.0.0","event":"vote","data":{{"delegators":[],"prop_id":{},"vote":"Approve","voter":"user-3.near"}}}}"#,
+                id
+            )]
+        );
+
+        ctx.predecessor_account_id = acc(1);
+        ctx.attached_deposit = 0;
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.reclaim_vote_storage(id, None),
+            Err(ReclaimError::NotFinalized)
+        );
+        assert_eq!(
+            ctr.reclaim_vote_storage(pre_vote_id, None),
+            Err(ReclaimError::NotFinalized)
+        );
+        assert_eq!(
+            ctr.reclaim_vote_storage(99, None),
+            Err(ReclaimError::PropNotFound)
+        );
+
+        ctx.block_timestamp += (VOTE_DURATION + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        let p = ctr.get_proposal(id).unwrap();
+        assert_eq!(ctr.reclaim_vote_storage(id, None), Ok(1));
+        assert_eq!(ctr.get_vote(id, acc(1)), None);
+        assert!(ctr.get_vote(id, acc(2)).is_some());
+        // index entries are removed
+        let voters: Vec<AccountId> = ctr
+            .get_votes(id, 0, 10)
+            .into_iter()
+            .map(|v| v.voter)
+            .collect();
+        assert_eq!(voters, vec![acc(2), acc(3)]);
+        assert!(ctr.votes_by_account(acc(1), 0, 10).is_empty());
+        assert!(ctr.vote_index.get(&(id, acc(1))).is_none());
+        assert_eq!(ctr.account_votes_len.get(&acc(1)), None);
+
+        // batch removal, accounts without a vote are skipped
+        ctx.predecessor_account_id = acc(9);
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.reclaim_vote_storage(id, Some(vec![acc(1), acc(2), acc(3), acc(4)])),
+            Ok(2)
+        );
+        assert_eq!(ctr.get_vote(id, acc(2)), None);
+        assert_eq!(ctr.get_vote(id, acc(3)), None);
+        assert!(ctr.get_votes(id, 0, 10).is_empty());
+        // the tally and the votes hash chain are preserved
+        assert_eq!(ctr.get_proposal(id).unwrap(), p);
+    }
+
+    fn commit(
+        mut ctx: VMContext,
+        ctr: &mut Contract,
+        voter: AccountId,
+        prop_id: u32,
+        vote: Vote,
+        salt: &[u8],
+    ) -> Result<(), VoteError> {
+        ctx.predecessor_account_id = iah_registry();
+        ctx.attached_deposit = VOTE_DEPOSIT;
+        testing_env!(ctx.clone());
+        let mut data = (&voter, &vote).try_to_vec().unwrap();
+        data.extend(salt);
+        let payload = CommitPayload {
+            prop_id,
+            commitment: env::sha256(&data).into(),
+        };
+        ctr.commit_vote(voter, min_vote_lock(&ctx), None, payload)
+    }
+
+    #[test]
+    fn secret_ballot() {
+        let (mut ctx, mut ctr, public_id) = setup_ctr(BOND);
+        let mut payload = create_prop_payload(PropKind::Text, "secret".to_string());
+        payload.secret_ballot = true;
+        ctx.attached_deposit = BOND;
+        testing_env!(ctx.clone());
+        let id = prop_id(ctr.create_proposal(acc(1), iah_proof(), payload));
+        assert_eq!(
+            ctr.get_proposal(id).unwrap().proposal.reveal_duration,
+            Some(REVEAL_DURATION)
+        );
+
+        let locked = min_vote_lock(&ctx);
+        assert_eq!(
+            ctr.vote(acc(1), locked, None, vote_payload(id, Vote::Approve)),
+            Err(VoteError::SecretBallot)
+        );
+        assert_eq!(
+            commit(
+                ctx.clone(),
+                &mut ctr,
+                acc(1),
+                public_id,
+                Vote::Approve,
+                b"s"
+            ),
+            Err(VoteError::NotSecretBallot)
+        );
+        for (a, v) in [
+            (1, Vote::Reject),
+            (1, Vote::Approve),
+            (2, Vote::Approve),
+            (3, Vote::Reject),
+        ] {
+            assert_eq!(
+                commit(ctx.clone(), &mut ctr, acc(a), id, v, b"salt"),
+                Ok(())
+            );
+        }
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!((p.commits, p.approve, p.reject), (3, 0, 0));
+
+        ctx.predecessor_account_id = acc(1);
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.reveal_vote(id, Vote::Approve, b"salt".to_vec().into()),
+            Err(VoteError::NotRevealTime)
+        );
+
+        // reveal window
+        ctx.block_timestamp = START + (VOTE_DURATION + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert_eq!(
+            commit(ctx.clone(), &mut ctr, acc(4), id, Vote::Approve, b"salt"),
+            Err(VoteError::Timeout)
+        );
+        ctx.predecessor_account_id = acc(1);
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.reveal_vote(id, Vote::Approve, b"bad".to_vec().into()),
+            Err(VoteError::BadReveal)
+        );
+        assert_eq!(
+            ctr.reveal_vote(id, Vote::Reject, b"salt".to_vec().into()),
+            Err(VoteError::BadReveal)
+        );
+        assert_eq!(
+            ctr.reveal_vote(id, Vote::Approve, b"salt".to_vec().into()),
+            Ok(())
+        );
+        ctx.predecessor_account_id = acc(2);
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.reveal_vote(id, Vote::Approve, b"salt".to_vec().into()),
+            Ok(())
+        );
+        ctx.predecessor_account_id = acc(4);
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.reveal_vote(id, Vote::Approve, b"salt".to_vec().into()),
+            Err(VoteError::NoCommitment)
+        );
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!(p.status, ProposalStatus::InProgress);
+        assert_eq!((p.commits, p.approve, p.reject), (1, 2, 0));
+        assert_eq!(ctr.get_vote(id, acc(1)).unwrap().vote, Vote::Approve);
+
+        // not revealed vote counts as abstain toward the quorum
+        ctx.block_timestamp = START + (VOTE_DURATION + REVEAL_DURATION + 1) * MSECOND;
+        ctx.predecessor_account_id = acc(3);
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.reveal_vote(id, Vote::Reject, b"salt".to_vec().into()),
+            Err(VoteError::NotRevealTime)
+        );
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!(p.status, ProposalStatus::Approved);
+    }
+
+    #[test]
+    fn multiple_choice() {
+        let (mut ctx, mut ctr, text_id) = setup_ctr(BOND);
+        let options = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let mut create = |ctr: &mut Contract, options: Vec<String>, method: TallyMethod| {
+            ctx.predecessor_account_id = iah_registry();
+            ctx.attached_deposit = BOND;
+            testing_env!(ctx.clone());
+            ctr.create_proposal(
+                acc(1),
+                iah_proof(),
+                create_prop_payload(
+                    PropKind::MultipleChoice { options, method },
+                    "drafts".to_string(),
+                ),
+            )
+        };
+        assert!(matches!(
+            create(&mut ctr, vec!["a".to_string()], TallyMethod::Plurality),
+            Err(CreatePropError::BadRequest(_))
+        ));
+        let irv = prop_id(create(
+            &mut ctr,
+            options.clone(),
+            TallyMethod::InstantRunoff,
+        ));
+        let plurality = prop_id(create(&mut ctr, options, TallyMethod::Plurality));
+        let options4 = vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+            "d".to_string(),
+        ];
+        let irv4 = prop_id(create(&mut ctr, options4, TallyMethod::InstantRunoff));
+
+        ctx.predecessor_account_id = iah_registry();
+        ctx.attached_deposit = VOTE_DEPOSIT;
+        testing_env!(ctx.clone());
+        let locked = min_vote_lock(&ctx);
+        for (id, v) in [
+            (irv, Vote::Approve),
+            (irv, Vote::Rank(vec![3])),
+            (irv, Vote::Rank(vec![0, 0])),
+            (irv, Vote::Rank(vec![])),
+            // at most MAX_RANKING options
+            (irv4, Vote::Rank(vec![0, 1, 2, 3])),
+            (plurality, Vote::Rank(vec![0, 1])),
+            (text_id, Vote::Rank(vec![0])),
+        ] {
+            assert_eq!(
+                ctr.vote(acc(1), locked, None, vote_payload(id, v)),
+                Err(VoteError::BadVote)
+            );
+        }
+
+        for (a, v) in [
+            (1, Vote::Rank(vec![0, 1])),
+            (2, Vote::Rank(vec![1, 0])),
+            (3, Vote::Rank(vec![2, 1])),
+            (4, Vote::Rank(vec![2])),
+            (4, Vote::Rank(vec![1])), // vote change
+        ] {
+            assert_eq!(ctr.vote(acc(a), locked, None, vote_payload(irv, v)), Ok(()));
+        }
+        for (a, v) in [
+            (1, Vote::Rank(vec![0])),
+            (2, Vote::Rank(vec![1])),
+            (3, Vote::Abstain),
+        ] {
+            assert_eq!(
+                ctr.vote(acc(a), locked, None, vote_payload(plurality, v)),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            ctr.get_choice_result(irv),
+            Some(ChoiceResult {
+                rounds: vec![vec![1, 2, 1], vec![0, 4, 0]],
+                winner: Some(1),
+            })
+        );
+        assert_eq!(
+            ctr.get_choice_result(plurality),
+            Some(ChoiceResult {
+                rounds: vec![vec![1, 1, 0]],
+                winner: None,
+            })
+        );
+        assert_eq!(ctr.get_choice_result(text_id), None);
+
+        ctx.block_timestamp += (VOTE_DURATION + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        let p = ctr.get_proposal(irv).unwrap().proposal;
+        assert_eq!(p.status, ProposalStatus::Approved);
+        // ballots without votes are kept in the index
+        assert_eq!((p.approve, p.ballots), (4, 5));
+        // tie: no winner
+        let p = ctr.get_proposal(plurality).unwrap().proposal;
+        assert_eq!(p.status, ProposalStatus::Rejected);
+    }
+
+    #[test]
+    fn get_votes() {
+        let (ctx, mut ctr, id1) = setup_ctr(BOND);
+        let id2 = create_proposal(ctx.clone(), &mut ctr, BOND);
+        vote(
+            ctx.clone(),
+            &mut ctr,
+            vec![acc(1), acc(2)],
+            id1,
+            Vote::Approve,
+        );
+        vote(ctx.clone(), &mut ctr, vec![acc(1)], id2, Vote::Reject);
+        // vote change doesn't add a new index entry
+        vote(ctx.clone(), &mut ctr, vec![acc(1)], id1, Vote::Abstain);
+
+        let votes = ctr.get_votes(id1, 0, 10);
+        assert_eq!(
+            votes.iter().map(|v| v.voter.clone()).collect::<Vec<_>>(),
+            vec![acc(1), acc(2)]
+        );
+        assert_eq!(votes[0].record.vote, Vote::Abstain);
+        assert_eq!(votes[1].record, ctr.get_vote(id1, acc(2)).unwrap());
+        assert_eq!(ctr.get_votes(id1, 1, 1)[0].voter, acc(2));
+        assert!(ctr.get_votes(id1, 2, 10).is_empty());
+        assert_eq!(ctr.get_proposal(id1).unwrap().proposal.voters, 2);
+
+        let votes = ctr.votes_by_account(acc(1), 0, 10);
+        assert_eq!(
+            votes
+                .iter()
+                .map(|v| (v.prop_id, v.record.vote.clone()))
+                .collect::<Vec<_>>(),
+            vec![(id1, Vote::Abstain), (id2, Vote::Reject)]
+        );
+        assert_eq!(ctr.votes_by_account(acc(1), 1, 1)[0].prop_id, id2);
+        assert!(ctr.votes_by_account(acc(3), 0, 10).is_empty());
+    }
+
     #[test]
     fn get_proposals() {
         let (ctx, mut ctr, id1) = setup_ctr(BOND);
@@ -1322,90 +2379,737 @@ mod unit_tests {
     }
 
     #[test]
-    fn update_consent() {
-        let (mut ctx, mut ctr, _) = setup_ctr(BOND);
-        ctx.predecessor_account_id = admin();
+    fn unsupport_proposal() {
+        let (mut ctx, mut ctr, id) = setup_ctr(PRE_BOND);
+        let locked = min_prevote_lock(&ctx);
+        assert_eq!(ctr.support_proposal(acc(1), locked, None, id), Ok(true));
+        assert_eq!(ctr.support_proposal(acc(2), locked, None, id), Ok(true));
+
+        assert_eq!(
+            ctr.unsupport_proposal(acc(3), iah_proof(), id),
+            Err(PrevoteError::NotSupported)
+        );
+        assert_eq!(ctr.unsupport_proposal(acc(1), iah_proof(), id), Ok(true));
+        let p = ctr.assert_pre_vote_prop(id).unwrap();
+        assert_eq!(p.support, 1);
+        assert!(!p.supported.contains(&acc(1)));
+        assert!(p.supported.contains(&acc(2)));
+        assert_eq!(
+            ctr.unsupport_proposal(acc(1), iah_proof(), id),
+            Err(PrevoteError::NotSupported)
+        );
+        // support can be given again
+        assert_eq!(ctr.support_proposal(acc(1), locked, None, id), Ok(true));
+
+        ctx.predecessor_account_id = acc(1);
         testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.unsupport_proposal(acc(1), iah_proof(), id),
+            Err(PrevoteError::NotIAHreg)
+        );
 
-        let c1 = Consent {
-            quorum: 11,
-            threshold: 1,
-        };
-        let c2 = Consent {
-            quorum: 12,
-            threshold: 2,
-        };
-        ctr.admin_update_consent(c1, c2);
-        assert_eq!(c1, ctr.simple_consent);
-        assert_eq!(c2, ctr.super_consent);
+        // overdue proposal is slashed
+        ctx.predecessor_account_id = iah_registry();
+        ctx.block_timestamp += (PRE_VOTE_DURATION + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.unsupport_proposal(acc(2), iah_proof(), id), Ok(false));
+        assert_eq!(ctr.get_proposal(id), None);
     }
 
     #[test]
-    fn update_white_list() {
+    fn ratify_constitution() {
         let (mut ctx, mut ctr, _) = setup_ctr(BOND);
-        ctx.predecessor_account_id = admin();
+        let ratify = |hash: u8, version: u32| PropKind::RatifyConstitution {
+            document_hash: vec![hash; 32].into(),
+            uri: "ipfs://constitution".to_string(),
+            version,
+        };
+        assert!(matches!(
+            ratify(1, 1).required_consent(),
+            ConsentKind::Super
+        ));
+        let create = |ctx: &mut VMContext, ctr: &mut Contract, kind: PropKind| {
+            ctx.predecessor_account_id = iah_registry();
+            ctx.attached_deposit = BOND;
+            testing_env!(ctx.clone());
+            ctr.create_proposal(
+                acc(1),
+                iah_proof(),
+                create_prop_payload(kind, "constitution".to_string()),
+            )
+        };
+        assert!(matches!(
+            create(&mut ctx, &mut ctr, ratify(1, 0)),
+            Err(CreatePropError::BadRequest(_))
+        ));
+        let v1 = prop_id(create(&mut ctx, &mut ctr, ratify(1, 1)));
+        let v1_again = prop_id(create(&mut ctx, &mut ctr, ratify(2, 1)));
+        assert_eq!(ctr.current_constitution(), None);
+
+        let accs: Vec<AccountId> = (1..=5).map(acc).collect();
+        vote(ctx.clone(), &mut ctr, accs.clone(), v1, Vote::Approve);
+        vote(ctx.clone(), &mut ctr, accs, v1_again, Vote::Approve);
+        ctx.block_timestamp += (VOTE_DURATION + 1) * MSECOND;
         testing_env!(ctx.clone());
+        assert!(ctr.execute(v1).is_ok());
+        let c1 = Constitution {
+            document_hash: vec![1; 32].into(),
+            uri: "ipfs://constitution".to_string(),
+            version: 1,
+            prop_id: v1,
+            ratified_at: ctx.block_timestamp / MSECOND,
+        };
+        assert_eq!(ctr.current_constitution(), Some(c1.clone()));
+        assert_eq!(ctr.constitutions(0, 10), vec![c1]);
 
-        assert_eq!(ctr.is_iom_whitelisted(&acc(1)), false);
-        ctr.admin_add_to_whitelist(acc(1));
-        assert_eq!(ctr.is_iom_whitelisted(&acc(1)), true);
-        ctr.admin_remove_from_whitelist(acc(1));
-        assert_eq!(ctr.is_iom_whitelisted(&acc(1)), false);
+        // version 1 is already ratified
+        assert!(matches!(
+            ctr.execute(v1_again),
+            Err(ExecError::StaleConstitution)
+        ));
+        assert!(matches!(
+            create(&mut ctx, &mut ctr, ratify(3, 1)),
+            Err(CreatePropError::BadRequest(_))
+        ));
+        let v2 = prop_id(create(&mut ctx, &mut ctr, ratify(3, 2)));
+        assert_eq!(ctr.constitutions(1, 10), vec![]);
+        assert!(ctr.get_proposal(v2).is_some());
     }
 
     #[test]
-    fn whitelisted_can_vote() {
-        let (mut ctx, mut ctr, id) = setup_ctr(BOND);
-        ctx.predecessor_account_id = admin();
-        testing_env!(ctx.clone());
-        ctr.admin_add_to_whitelist(acc(1));
-        ctx.predecessor_account_id = acc(1);
-        ctx.attached_deposit = VOTE_DEPOSIT;
+    fn withdraw_proposal() {
+        let (mut ctx, mut ctr, id) = setup_ctr(PRE_BOND);
+        let active_id = create_proposal(ctx.clone(), &mut ctr, BOND);
+        let voted_id = create_proposal(ctx.clone(), &mut ctr, BOND);
+        vote(ctx.clone(), &mut ctr, vec![acc(2)], voted_id, Vote::Approve);
+
+        ctx.predecessor_account_id = acc(2);
+        ctx.attached_deposit = 0;
         testing_env!(ctx.clone());
-        ctr.vote_whitelist(vote_payload(id, Vote::Approve));
-    }
+        assert_eq!(ctr.withdraw_proposal(id), Err(WithdrawError::NotProposer));
+        assert_eq!(ctr.withdraw_proposal(10), Err(WithdrawError::NotFound));
 
-    #[test]
-    fn not_called_by_iah_registry() {
-        let (mut ctx, mut ctr, id) = setup_ctr(BOND);
-        let locked = min_vote_lock(&ctx);
         ctx.predecessor_account_id = acc(1);
-        testing_env!(ctx);
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.withdraw_proposal(id), Ok(()));
+        assert_eq!(ctr.get_proposal(id), None);
+        assert_eq!(ctr.withdraw_proposal(id), Err(WithdrawError::NotFound));
         assert_eq!(
-            ctr.vote(acc(1), locked, None, vote_payload(id, Vote::Approve)),
-            Err(VoteError::NotIAHreg)
+            ctr.withdraw_proposal(voted_id),
+            Err(WithdrawError::AlreadyVoted)
         );
+        assert_eq!(ctr.withdraw_proposal(active_id), Ok(()));
+        assert_eq!(ctr.get_proposal(active_id), None);
 
-        let resp = ctr.create_proposal(
-            acc(1),
-            iah_proof(),
-            create_prop_payload(PropKind::Text, "Proposal unit test".to_string()),
+        // ranked votes are not counted in approve/reject/abstain/spam
+        ctx.predecessor_account_id = iah_registry();
+        ctx.attached_deposit = BOND;
+        let choice_id = create_prop(
+            &ctx,
+            &mut ctr,
+            create_prop_payload(
+                PropKind::MultipleChoice {
+                    options: vec!["a".to_string(), "b".to_string()],
+                    method: TallyMethod::Plurality,
+                },
+                "drafts".to_string(),
+            ),
+            false,
         );
-        assert_eq!(resp, Err(CreatePropError::NotIAHreg));
-
-        let resp = ctr.support_proposal(acc(1), locked, None, id);
-        assert_eq!(resp, Err(PrevoteError::NotIAHreg));
-    }
-
-    #[test]
-    fn iah_lock_not_enough() {
-        let (ctx, mut ctr, id) = setup_ctr(BOND);
-        let locked = min_vote_lock(&ctx) - 1;
+        vote(
+            ctx.clone(),
+            &mut ctr,
+            vec![acc(2)],
+            choice_id,
+            Vote::Rank(vec![1]),
+        );
+        ctx.predecessor_account_id = acc(1);
+        ctx.attached_deposit = 0;
+        testing_env!(ctx.clone());
         assert_eq!(
-            ctr.vote(acc(1), locked, None, vote_payload(id, Vote::Approve)),
-            Err(VoteError::LockedUntil)
+            ctr.withdraw_proposal(choice_id),
+            Err(WithdrawError::AlreadyVoted)
         );
 
-        let locked = min_prevote_lock(&ctx) - 1;
-        let id2 = create_proposal(ctx, &mut ctr, PRE_BOND);
-        let resp = ctr.support_proposal(acc(1), locked, None, id2);
-        assert_eq!(resp, Err(PrevoteError::LockedUntil));
+        // overdue pre-vote proposal can only be slashed
+        let id = create_proposal(ctx.clone(), &mut ctr, PRE_BOND);
+        ctx.predecessor_account_id = acc(1);
+        ctx.attached_deposit = 0;
+        ctx.block_timestamp += (PRE_VOTE_DURATION + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.withdraw_proposal(id), Err(WithdrawError::Overdue));
     }
 
     #[test]
-    fn support_proposal_by_congress() {
-        let (_, mut ctr, id) = setup_ctr(PRE_BOND);
-
+    fn update_consent() {
+        let (mut ctx, mut ctr, _) = setup_ctr(BOND);
+        ctx.predecessor_account_id = admin();
+        testing_env!(ctx.clone());
+
+        let c1 = Consent {
+            quorum: 11,
+            threshold: 1,
+            quorum_percent: None,
+        };
+        let c2 = Consent {
+            quorum: 12,
+            threshold: 2,
+            quorum_percent: None,
+        };
+        ctr.admin_update_consent(c1, c2);
+        assert_eq!(c1, ctr.simple_consent);
+        assert_eq!(c2, ctr.super_consent);
+    }
+
+    #[test]
+    fn update_consent_proposal() {
+        let (mut ctx, mut ctr, _) = setup_ctr(BOND);
+        let create = |ctx: &mut VMContext, ctr: &mut Contract, kind: PropKind| {
+            ctx.predecessor_account_id = iah_registry();
+            ctx.attached_deposit = BOND;
+            testing_env!(ctx.clone());
+            ctr.create_proposal(
+                acc(1),
+                iah_proof(),
+                create_prop_payload(kind, "config".to_string()),
+            )
+        };
+        let simple = Consent {
+            quorum: 4,
+            threshold: 55,
+            quorum_percent: Some(5),
+        };
+        let update = |simple: Consent, sup: Consent| PropKind::UpdateConsent {
+            simple_consent: simple,
+            super_consent: sup,
+        };
+        for kind in [
+            // threshold below MIN_THRESHOLD
+            update(
+                Consent {
+                    threshold: 10,
+                    ..simple
+                },
+                simple,
+            ),
+            // not a percentage
+            update(
+                simple,
+                Consent {
+                    threshold: 101,
+                    ..simple
+                },
+            ),
+            // super weaker than simple
+            update(
+                simple,
+                Consent {
+                    quorum: 3,
+                    ..simple
+                },
+            ),
+            update(
+                simple,
+                Consent {
+                    quorum_percent: None,
+                    ..simple
+                },
+            ),
+            PropKind::UpdatePreVoteSupport {
+                pre_vote_support: 0,
+            },
+            PropKind::UpdatePreVoteSupport {
+                pre_vote_support: MAX_PRE_VOTE_SUPPORT + 1,
+            },
+        ] {
+            assert!(matches!(
+                create(&mut ctx, &mut ctr, kind),
+                Err(CreatePropError::BadRequest(_))
+            ));
+        }
+
+        let sup = Consent {
+            quorum: 6,
+            threshold: 70,
+            quorum_percent: Some(10),
+        };
+        let consent_id = prop_id(create(&mut ctx, &mut ctr, update(simple, sup)));
+        let support_id = prop_id(create(
+            &mut ctx,
+            &mut ctr,
+            PropKind::UpdatePreVoteSupport {
+                pre_vote_support: 20,
+            },
+        ));
+        let accs: Vec<AccountId> = (1..=5).map(acc).collect();
+        vote(
+            ctx.clone(),
+            &mut ctr,
+            accs.clone(),
+            consent_id,
+            Vote::Approve,
+        );
+        vote(ctx.clone(), &mut ctr, accs, support_id, Vote::Approve);
+        ctx.block_timestamp += (VOTE_DURATION + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert!(ctr.execute(consent_id).is_ok());
+        assert!(ctr.execute(support_id).is_ok());
+        assert_eq!(ctr.simple_consent, simple);
+        assert_eq!(ctr.super_consent, sup);
+        assert_eq!(ctr.pre_vote_support, 20);
+    }
+
+    #[test]
+    fn quorum_from_population() {
+        let (mut ctx, mut ctr, _) = setup_ctr(BOND);
+        let c = Consent {
+            quorum: 3,
+            threshold: 50,
+            quorum_percent: Some(7),
+        };
+        assert_eq!(c.quorum_for(0), 3);
+        assert_eq!(c.quorum_for(1000), 70);
+        assert_eq!(c.quorum_for(1001), 71, "should round up");
+
+        ctx.predecessor_account_id = admin();
+        testing_env!(ctx.clone());
+        ctr.admin_update_consent(c, ctr.super_consent);
+        ctr.admin_set_population_class(iah_registry(), 1);
+        ctx.predecessor_account_id = ctx.current_account_id.clone();
+        testing_env!(ctx.clone());
+        ctr.on_population_update(Ok(1000));
+        assert_eq!(
+            ctr.config().population,
+            Some(Population {
+                issuer: iah_registry(),
+                class: 1,
+                count: 1000,
+                updated_at: START / MSECOND,
+            })
+        );
+
+        // quorum is snapshotted when the proposal enters the active queue
+        let id_active = create_proposal(ctx.clone(), &mut ctr, BOND);
+        let id_prevote = create_proposal(ctx.clone(), &mut ctr, PRE_BOND);
+        assert_eq!(
+            ctr.get_proposal(id_active).unwrap().proposal.quorum,
+            Some(70)
+        );
+        assert_eq!(ctr.get_proposal(id_prevote).unwrap().proposal.quorum, None);
+
+        ctx.predecessor_account_id = ctx.current_account_id.clone();
+        testing_env!(ctx.clone());
+        ctr.on_population_update(Ok(2000));
+        assert_eq!(ctr.on_support_by_congress(Ok(true), id_prevote), Ok(true));
+        assert_eq!(
+            ctr.get_proposal(id_active).unwrap().proposal.quorum,
+            Some(70)
+        );
+        assert_eq!(
+            ctr.get_proposal(id_prevote).unwrap().proposal.quorum,
+            Some(140)
+        );
+
+        // 3 votes are not enough to reach the quorum
+        vote_and_fast_forward_status_check(
+            &mut ctx,
+            &mut ctr,
+            vec![acc(1), acc(2), acc(3)],
+            id_active,
+            Vote::Approve,
+            ProposalStatus::Rejected,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "quorum_percent must be a percentage (0-100%)")]
+    fn update_consent_invalid_quorum_percent() {
+        let (mut ctx, mut ctr, _) = setup_ctr(BOND);
+        ctx.predecessor_account_id = admin();
+        testing_env!(ctx.clone());
+        let c = Consent {
+            quorum: 3,
+            threshold: 50,
+            quorum_percent: Some(101),
+        };
+        ctr.admin_update_consent(c, c);
+    }
+
+    const DELEGATION_DEPOSIT: u128 = ONE_NEAR / 10;
+
+    fn delegate(
+        mut ctx: VMContext,
+        ctr: &mut Contract,
+        delegator: AccountId,
+        delegate: AccountId,
+        locked_until: u64,
+    ) {
+        ctx.predecessor_account_id = iah_registry();
+        ctx.attached_deposit = DELEGATION_DEPOSIT;
+        testing_env!(ctx);
+        assert_eq!(
+            ctr.delegate(delegator, locked_until, None, delegate),
+            Ok(())
+        );
+    }
+
+    fn accept_delegations(mut ctx: VMContext, ctr: &mut Contract, delegate: AccountId) {
+        ctx.predecessor_account_id = delegate.clone();
+        ctx.attached_deposit = ONE_NEAR / 100;
+        testing_env!(ctx);
+        assert_eq!(ctr.accept_delegations(true), Ok(()));
+        assert!(ctr.accepts_delegations(delegate));
+    }
+
+    #[test]
+    fn delegation() {
+        let (mut ctx, mut ctr, id) = setup_ctr(BOND);
+        let lock = min_vote_lock(&ctx);
+        // delegate must accept delegations
+        ctx.predecessor_account_id = iah_registry();
+        ctx.attached_deposit = DELEGATION_DEPOSIT;
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.delegate(acc(2), lock, None, acc(1)),
+            Err(DelegateError::NotAccepted)
+        );
+        accept_delegations(ctx.clone(), &mut ctr, acc(1));
+        accept_delegations(ctx.clone(), &mut ctr, acc(6));
+
+        delegate(ctx.clone(), &mut ctr, acc(2), acc(1), lock);
+        delegate(ctx.clone(), &mut ctr, acc(3), acc(1), lock);
+        // lock is too short: the delegation won't be counted
+        delegate(ctx.clone(), &mut ctr, acc(4), acc(1), lock - 1);
+        // acc(5) delegates to acc(1), and then changes the delegation
+        delegate(ctx.clone(), &mut ctr, acc(5), acc(1), lock);
+        delegate(ctx.clone(), &mut ctr, acc(5), acc(6), lock);
+        assert_eq!(ctr.get_delegators(acc(1)), vec![acc(2), acc(3), acc(4)]);
+        let d = ctr.get_delegation(acc(5)).unwrap();
+        assert_eq!((d.delegate, d.locked_until), (acc(6), lock));
+        // the deposit minus the delegation storage is kept for the delegated votes
+        assert!(d.deposit.0 > VOTE_DEPOSIT && d.deposit.0 < DELEGATION_DEPOSIT);
+        assert_eq!(
+            ctr.delegate(acc(1), lock, None, acc(1)),
+            Err(DelegateError::SelfDelegation)
+        );
+
+        // direct vote is not overwritten by the delegate
+        vote(ctx.clone(), &mut ctr, vec![acc(3)], id, Vote::Reject);
+        // delegators pay the storage for the delegated votes
+        let deposit = ctr.get_delegation(acc(2)).unwrap().deposit.0;
+        vote(ctx.clone(), &mut ctr, vec![acc(1)], id, Vote::Approve);
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!((p.approve, p.reject), (2, 1));
+        let mut expected = vote_record(ctx.block_timestamp, Vote::Approve);
+        expected.delegate = Some(acc(1));
+        assert_eq!(ctr.get_vote(id, acc(2)), Some(expected));
+        assert_eq!(ctr.get_vote(id, acc(3)).unwrap().delegate, None);
+        assert_eq!(ctr.get_vote(id, acc(4)), None);
+        let used = deposit - ctr.get_delegation(acc(2)).unwrap().deposit.0;
+        assert!(used > 0 && used < VOTE_DEPOSIT);
+
+        // delegator overwrites the delegated vote
+        vote(ctx.clone(), &mut ctr, vec![acc(2)], id, Vote::Spam);
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!((p.approve, p.reject, p.spam), (1, 1, 1));
+
+        // delegate changes the vote: only its own vote changes
+        vote(ctx.clone(), &mut ctr, vec![acc(1)], id, Vote::Abstain);
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!((p.approve, p.reject, p.spam, p.abstain), (0, 1, 1, 1));
+
+        // undelegate
+        ctx.predecessor_account_id = acc(2);
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.undelegate(), Ok(()));
+        assert_eq!(ctr.undelegate(), Err(DelegateError::NotDelegated));
+        assert_eq!(ctr.get_delegation(acc(2)), None);
+        assert_eq!(ctr.get_delegators(acc(1)), vec![acc(3), acc(4)]);
+
+        // only the delegate can remove a delegator
+        assert_eq!(
+            ctr.remove_delegator(acc(3)),
+            Err(DelegateError::NotDelegated)
+        );
+        ctx.predecessor_account_id = acc(1);
+        testing_env!(ctx.clone());
+        assert_eq!(ctr.remove_delegator(acc(3)), Ok(()));
+        assert_eq!(ctr.get_delegation(acc(3)), None);
+        assert_eq!(ctr.get_delegators(acc(1)), vec![acc(4)]);
+
+        // new delegations are rejected once the delegate stops accepting them
+        assert_eq!(ctr.accept_delegations(false), Ok(()));
+        assert!(!ctr.accepts_delegations(acc(1)));
+        ctx.predecessor_account_id = iah_registry();
+        ctx.attached_deposit = DELEGATION_DEPOSIT;
+        testing_env!(ctx.clone());
+        assert_eq!(
+            ctr.delegate(acc(3), lock, None, acc(1)),
+            Err(DelegateError::NotAccepted)
+        );
+        assert_eq!(ctr.get_delegators(acc(1)), vec![acc(4)]);
+    }
+
+    #[test]
+    fn delegation_deposit() {
+        let (ctx, mut ctr, id) = setup_ctr(BOND);
+        let lock = min_vote_lock(&ctx);
+        accept_delegations(ctx.clone(), &mut ctr, acc(1));
+        delegate(ctx.clone(), &mut ctr, acc(2), acc(1), lock);
+        // deposit doesn't cover a delegated vote
+        let mut ctx2 = ctx.clone();
+        ctx2.predecessor_account_id = iah_registry();
+        ctx2.attached_deposit = VOTE_DEPOSIT;
+        testing_env!(ctx2);
+        assert_eq!(ctr.delegate(acc(3), lock, None, acc(1)), Ok(()));
+        assert!(ctr.get_delegation(acc(3)).unwrap().deposit.0 < VOTE_DEPOSIT);
+
+        vote(ctx.clone(), &mut ctr, vec![acc(1)], id, Vote::Approve);
+        assert_eq!(ctr.get_vote(id, acc(2)).unwrap().delegate, Some(acc(1)));
+        assert_eq!(ctr.get_vote(id, acc(3)), None);
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!(p.approve, 2);
+        // direct vote replaces the delegated record paid by the delegator
+        vote(ctx.clone(), &mut ctr, vec![acc(2)], id, Vote::Reject);
+        assert_eq!(ctr.get_vote(id, acc(2)).unwrap().delegate, None);
+
+        // vote storage is refunded to the delegator, who paid for it
+        let mut ctx = ctx.clone();
+        ctx.block_timestamp += (VOTE_DURATION + 1) * MSECOND;
+        ctx.predecessor_account_id = acc(5);
+        testing_env!(ctx);
+        assert_eq!(ctr.reclaim_vote_storage(id, Some(vec![acc(2)])), Ok(1));
+        let receipts = near_sdk::test_utils::get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, acc(2));
+    }
+
+    #[test]
+    fn update_white_list() {
+        let (mut ctx, mut ctr, _) = setup_ctr(BOND);
+        ctx.predecessor_account_id = admin();
+        testing_env!(ctx.clone());
+
+        assert!(!ctr.is_iom_whitelisted(&acc(1)));
+        ctr.admin_add_to_whitelist(acc(1), wl_expiry(&ctx), wl_reason());
+        assert!(ctr.is_iom_whitelisted(&acc(1)));
+        ctr.admin_remove_from_whitelist(acc(1));
+        assert!(!ctr.is_iom_whitelisted(&acc(1)));
+    }
+
+    #[test]
+    fn whitelist_entries() {
+        let (mut ctx, mut ctr, id) = setup_ctr(BOND);
+        ctx.predecessor_account_id = admin();
+        testing_env!(ctx.clone());
+        let expiry = wl_expiry(&ctx);
+        ctr.admin_add_to_whitelist(acc(1), expiry, wl_reason());
+        ctr.admin_add_to_whitelist(acc(2), expiry + 1, wl_reason());
+        let entry = |expires_at| WhitelistEntry {
+            expires_at,
+            reason: wl_reason(),
+            authority: admin(),
+            added_at: START / MSECOND,
+        };
+        assert_eq!(
+            ctr.get_whitelist(0, 10),
+            vec![
+                WhitelistOutput {
+                    account: acc(1),
+                    entry: entry(expiry)
+                },
+                WhitelistOutput {
+                    account: acc(2),
+                    entry: entry(expiry + 1)
+                },
+            ]
+        );
+        assert_eq!(ctr.get_whitelist(1, 1).len(), 1);
+        assert_eq!(ctr.get_whitelist(2, 10), vec![]);
+
+        ctx.block_timestamp = expiry * MSECOND;
+        testing_env!(ctx.clone());
+        assert!(!ctr.is_iom_whitelisted(&acc(1)));
+        assert!(ctr.is_iom_whitelisted(&acc(2)));
+
+        ctx.predecessor_account_id = acc(2);
+        ctx.attached_deposit = VOTE_DEPOSIT;
+        testing_env!(ctx.clone());
+        ctr.vote_whitelist(vote_payload(id, Vote::Approve));
+    }
+
+    #[test]
+    #[should_panic(expected = "whitelist entry expired")]
+    fn whitelist_expired() {
+        let (mut ctx, mut ctr, id) = setup_ctr(BOND);
+        ctx.predecessor_account_id = admin();
+        testing_env!(ctx.clone());
+        ctr.admin_add_to_whitelist(acc(1), wl_expiry(&ctx), wl_reason());
+        ctx.predecessor_account_id = acc(1);
+        ctx.attached_deposit = VOTE_DEPOSIT;
+        ctx.block_timestamp = wl_expiry(&ctx) * MSECOND;
+        testing_env!(ctx.clone());
+        ctr.vote_whitelist(vote_payload(id, Vote::Approve));
+    }
+
+    #[test]
+    fn roles() {
+        let (mut ctx, mut ctr, _) = setup_ctr(BOND);
+        let create = |ctx: &mut VMContext, ctr: &mut Contract, kind: PropKind| {
+            ctx.predecessor_account_id = iah_registry();
+            ctx.attached_deposit = BOND;
+            testing_env!(ctx.clone());
+            ctr.create_proposal(
+                acc(1),
+                iah_proof(),
+                create_prop_payload(kind, "roles".to_string()),
+            )
+        };
+        let manager = acc(9);
+        let grant = PropKind::GrantRole {
+            account: manager.clone(),
+            role: Role::WhitelistManager,
+        };
+        let revoke = PropKind::RevokeRole {
+            account: manager.clone(),
+            role: Role::WhitelistManager,
+        };
+        assert!(matches!(grant.required_consent(), ConsentKind::Super));
+        assert!(matches!(revoke.required_consent(), ConsentKind::Simple));
+        assert!(matches!(
+            create(&mut ctx, &mut ctr, revoke.clone()),
+            Err(CreatePropError::BadRequest(_))
+        ));
+
+        let accs: Vec<AccountId> = (1..=5).map(acc).collect();
+        let id = prop_id(create(&mut ctx, &mut ctr, grant.clone()));
+        vote(ctx.clone(), &mut ctr, accs.clone(), id, Vote::Approve);
+        ctx.block_timestamp += (VOTE_DURATION + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert!(ctr.execute(id).is_ok());
+        assert!(ctr.has_role(&manager, Role::WhitelistManager));
+        assert!(!ctr.has_role(&manager, Role::ConsentUpdater));
+        let mut roles = admin_roles(&admin());
+        roles.insert(manager.clone(), vec![Role::WhitelistManager]);
+        assert_eq!(ctr.get_roles(), roles);
+        assert!(matches!(
+            create(&mut ctx, &mut ctr, grant),
+            Err(CreatePropError::BadRequest(_))
+        ));
+
+        ctx.predecessor_account_id = manager.clone();
+        testing_env!(ctx.clone());
+        ctr.admin_add_to_whitelist(acc(1), wl_expiry(&ctx), wl_reason());
+        assert!(ctr.is_iom_whitelisted(&acc(1)));
+
+        let id = prop_id(create(&mut ctx, &mut ctr, revoke));
+        vote(ctx.clone(), &mut ctr, accs, id, Vote::Approve);
+        ctx.block_timestamp += (VOTE_DURATION + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert!(ctr.execute(id).is_ok());
+        assert!(!ctr.has_role(&manager, Role::WhitelistManager));
+        assert_eq!(ctr.get_roles(), admin_roles(&admin()));
+    }
+
+    #[test]
+    #[should_panic(expected = "not authorized")]
+    fn admin_role_revoked() {
+        let (mut ctx, mut ctr, _) = setup_ctr(BOND);
+        assert!(ctr.has_role(&admin(), Role::WhitelistManager));
+        assert!(ctr.has_role(&admin(), Role::ConsentUpdater));
+
+        ctx.predecessor_account_id = iah_registry();
+        ctx.attached_deposit = BOND;
+        testing_env!(ctx.clone());
+        let kind = PropKind::RevokeRole {
+            account: admin(),
+            role: Role::WhitelistManager,
+        };
+        let id = prop_id(ctr.create_proposal(
+            acc(1),
+            iah_proof(),
+            create_prop_payload(kind, "retire admin".to_string()),
+        ));
+        vote(
+            ctx.clone(),
+            &mut ctr,
+            (1..=5).map(acc).collect(),
+            id,
+            Vote::Approve,
+        );
+        ctx.block_timestamp += (VOTE_DURATION + 1) * MSECOND;
+        testing_env!(ctx.clone());
+        assert!(ctr.execute(id).is_ok());
+        assert!(!ctr.has_role(&admin(), Role::WhitelistManager));
+        assert!(ctr.has_role(&admin(), Role::ConsentUpdater));
+
+        ctx.predecessor_account_id = admin();
+        testing_env!(ctx.clone());
+        ctr.admin_add_to_whitelist(acc(1), wl_expiry(&ctx), wl_reason());
+    }
+
+    #[test]
+    #[should_panic(expected = "not authorized")]
+    fn whitelist_not_authorized() {
+        let (mut ctx, mut ctr, _) = setup_ctr(BOND);
+        ctx.predecessor_account_id = acc(1);
+        testing_env!(ctx.clone());
+        ctr.admin_add_to_whitelist(acc(1), wl_expiry(&ctx), wl_reason());
+    }
+
+    #[test]
+    fn whitelisted_can_vote() {
+        let (mut ctx, mut ctr, id) = setup_ctr(BOND);
+        ctx.predecessor_account_id = admin();
+        testing_env!(ctx.clone());
+        ctr.admin_add_to_whitelist(acc(1), wl_expiry(&ctx), wl_reason());
+        ctx.predecessor_account_id = acc(1);
+        ctx.attached_deposit = VOTE_DEPOSIT;
+        testing_env!(ctx.clone());
+        ctr.vote_whitelist(vote_payload(id, Vote::Approve));
+    }
+
+    #[test]
+    fn not_called_by_iah_registry() {
+        let (mut ctx, mut ctr, id) = setup_ctr(BOND);
+        let locked = min_vote_lock(&ctx);
+        ctx.predecessor_account_id = acc(1);
+        testing_env!(ctx);
+        assert_eq!(
+            ctr.vote(acc(1), locked, None, vote_payload(id, Vote::Approve)),
+            Err(VoteError::NotIAHreg)
+        );
+
+        let resp = ctr.create_proposal(
+            acc(1),
+            iah_proof(),
+            create_prop_payload(PropKind::Text, "Proposal unit test".to_string()),
+        );
+        assert_eq!(resp.err(), Some(CreatePropError::NotIAHreg));
+
+        let resp = ctr.support_proposal(acc(1), locked, None, id);
+        assert_eq!(resp, Err(PrevoteError::NotIAHreg));
+    }
+
+    #[test]
+    fn iah_lock_not_enough() {
+        let (ctx, mut ctr, id) = setup_ctr(BOND);
+        let locked = min_vote_lock(&ctx) - 1;
+        assert_eq!(
+            ctr.vote(acc(1), locked, None, vote_payload(id, Vote::Approve)),
+            Err(VoteError::LockedUntil)
+        );
+
+        let locked = min_prevote_lock(&ctx) - 1;
+        let id2 = create_proposal(ctx, &mut ctr, PRE_BOND);
+        let resp = ctr.support_proposal(acc(1), locked, None, id2);
+        assert_eq!(resp, Err(PrevoteError::LockedUntil));
+    }
+
+    #[test]
+    fn support_proposal_by_congress() {
+        let (_, mut ctr, id) = setup_ctr(PRE_BOND);
+
         match ctr.support_proposal_by_congress(id, iah_registry()) {
             Err(PrevoteError::NotCongress) => (),
             _ => panic!("expected error: provided DAO must be one of the congress houses"),
@@ -1452,6 +3156,7 @@ mod unit_tests {
         // modify prop to expected values and see if it equals the stored one
         prop.proposal.status = ProposalStatus::InProgress;
         prop.proposal.start += 1; // start is in milliseconds
+        prop.proposal.quorum = Some(3);
         assert_eq!(ctr.get_proposal(id).unwrap(), prop);
     }
 
@@ -1480,6 +3185,224 @@ mod unit_tests {
         }
     }
 
+    fn on_create_proposal(
+        mut ctx: VMContext,
+        ctr: &mut Contract,
+        kind: PropKind,
+        resp: PromiseResult,
+    ) -> Option<u32> {
+        ctx.predecessor_account_id = ctx.current_account_id.clone();
+        ctx.attached_deposit = 0;
+        testing_env!(
+            ctx,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![resp],
+        );
+        ctr.on_create_proposal(
+            acc(1),
+            U128(PRE_BOND),
+            create_prop_payload(kind, "Proposal unit test".to_string()),
+        )
+    }
+
+    fn json_resp(v: serde_json::Value) -> PromiseResult {
+        PromiseResult::Successful(serde_json::to_vec(&v).unwrap())
+    }
+
+    #[test]
+    fn create_proposal_verified_in_congress() {
+        let (mut ctx, mut ctr, _) = setup_ctr(PRE_BOND);
+        let veto = PropKind::Veto {
+            dao: hom(),
+            prop_id: 2,
+        };
+        let budget = PropKind::ApproveBudget {
+            dao: coa(),
+            prop_id: 3,
+        };
+        let dismiss = PropKind::Dismiss {
+            dao: tc(),
+            member: acc(2),
+        };
+
+        ctx.predecessor_account_id = iah_registry();
+        ctx.attached_deposit = PRE_BOND;
+        testing_env!(ctx.clone());
+        let resp = ctr.create_proposal(
+            acc(1),
+            iah_proof(),
+            create_prop_payload(
+                PropKind::Dissolve { dao: acc(5) },
+                "Proposal unit test".to_string(),
+            ),
+        );
+        assert!(matches!(resp, Err(CreatePropError::BadRequest(_))));
+        for kind in [veto.clone(), budget.clone(), dismiss.clone()] {
+            let resp = ctr.create_proposal(
+                acc(1),
+                iah_proof(),
+                create_prop_payload(kind, "Proposal unit test".to_string()),
+            );
+            assert!(matches!(resp, Ok(PromiseOrValue::Promise(_))));
+        }
+        assert_eq!(ctr.number_of_proposals(), 1);
+
+        //
+        // verification failed: proposal is not created
+        let failed = vec![
+            (veto.clone(), json_resp(serde_json::json!([]))),
+            (
+                veto.clone(),
+                json_resp(serde_json::json!([{"id": 3, "veto_deadline": 1}])),
+            ),
+            (budget.clone(), json_resp(serde_json::json!(null))),
+            (
+                budget.clone(),
+                json_resp(
+                    serde_json::json!({"id": 3, "status": "InProgress", "kind": {"Budget": "100"}}),
+                ),
+            ),
+            (
+                budget.clone(),
+                json_resp(serde_json::json!({"id": 3, "status": "Approved", "kind": "Text"})),
+            ),
+            (dismiss.clone(), json_resp(serde_json::json!(false))),
+            (dismiss.clone(), PromiseResult::Failed),
+        ];
+        for (kind, resp) in failed {
+            assert_eq!(on_create_proposal(ctx.clone(), &mut ctr, kind, resp), None);
+        }
+        assert_eq!(ctr.number_of_proposals(), 1);
+
+        //
+        // verification passed
+        let passed = vec![
+            (
+                veto,
+                json_resp(serde_json::json!([{"id": 2, "veto_deadline": 1, "status": "Approved"}])),
+            ),
+            (
+                budget,
+                json_resp(
+                    serde_json::json!({"id": 3, "status": "Approved", "kind": {"Budget": "100"}}),
+                ),
+            ),
+            (dismiss, json_resp(serde_json::json!(true))),
+        ];
+        for (i, (kind, resp)) in passed.into_iter().enumerate() {
+            let id = on_create_proposal(ctx.clone(), &mut ctr, kind.clone(), resp);
+            assert_eq!(id, Some(i as u32 + 2));
+            let p = ctr.get_proposal(id.unwrap()).unwrap().proposal;
+            assert_eq!(p.kind, kind);
+            assert_eq!(p.status, ProposalStatus::PreVote);
+            assert_eq!(p.bond, PRE_BOND);
+            assert!(p.proposal_storage > 0);
+        }
+    }
+
+    #[test]
+    fn create_proposal_storage_not_covered_by_bond() {
+        let (mut ctx, mut ctr, _) = setup_ctr(PRE_BOND);
+        let dismiss = PropKind::Dismiss {
+            dao: tc(),
+            member: acc(2),
+        };
+        ctx.predecessor_account_id = ctx.current_account_id.clone();
+        ctx.attached_deposit = 0;
+        let results = vec![
+            json_resp(serde_json::json!(true)),
+            json_resp(serde_json::json!(false)),
+            json_resp(serde_json::json!(false)),
+            json_resp(serde_json::json!(false)),
+        ];
+        testing_env!(
+            ctx,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            results,
+        );
+        // storage of a 400kB description costs more than the pre-vote bond
+        let id = ctr.on_create_proposal(
+            acc(1),
+            U128(PRE_BOND),
+            create_prop_payload(dismiss, "a".repeat(400_000)),
+        );
+        assert_eq!(id, None);
+        assert_eq!(ctr.number_of_proposals(), 1);
+        assert!(ctr.get_proposal(2).is_none());
+    }
+
+    #[test]
+    fn fast_track_congress_member() {
+        let (mut ctx, mut ctr, id) = setup_ctr(PRE_BOND);
+        assert_eq!(
+            ctr.get_proposal(id).unwrap().proposal.status,
+            ProposalStatus::PreVote
+        );
+
+        // the congress houses are queried for every proposal with a bond below
+        // active_queue_bond, proposals of other accounts go to the pre-vote queue.
+        ctx.attached_deposit = PRE_BOND;
+        ctx.predecessor_account_id = iah_registry();
+        testing_env!(ctx.clone());
+        let payload = create_prop_payload(PropKind::Text, "by congress".to_string());
+        assert!(matches!(
+            ctr.create_proposal(acc(1), iah_proof(), payload.clone()),
+            Ok(PromiseOrValue::Promise(_))
+        ));
+        let id = create_prop(&ctx, &mut ctr, payload.clone(), false);
+        assert_eq!(
+            ctr.get_proposal(id).unwrap().proposal.status,
+            ProposalStatus::PreVote
+        );
+
+        let id = create_prop(&ctx, &mut ctr, payload, true);
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!(p.status, ProposalStatus::InProgress);
+        assert_eq!(p.bond, PRE_BOND);
+        assert_eq!(p.quorum, Some(3));
+        assert!(ctr.pre_vote_proposals.get(&id).is_none());
+
+        // verified proposal: congress verification followed by is_member responses
+        let dismiss = PropKind::Dismiss {
+            dao: tc(),
+            member: acc(2),
+        };
+        ctx.predecessor_account_id = ctx.current_account_id.clone();
+        ctx.attached_deposit = 0;
+        testing_env!(
+            ctx.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![
+                json_resp(serde_json::json!(true)),
+                json_resp(serde_json::json!(true)),
+                json_resp(serde_json::json!(false)),
+                PromiseResult::Failed,
+            ],
+        );
+        let id = ctr
+            .on_create_proposal(
+                acc(1),
+                U128(PRE_BOND),
+                create_prop_payload(dismiss, "dismiss".to_string()),
+            )
+            .unwrap();
+        let p = ctr.get_proposal(id).unwrap().proposal;
+        assert_eq!(p.status, ProposalStatus::InProgress);
+        assert_eq!(
+            near_sdk::test_utils::get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"ndc-congress","version":"1.0.0","event":"proposal-create","data":{{"active":true,"kind":"dismiss","prop_id":{}}}}}"#,
+                id
+            )]
+        );
+    }
+
     #[test]
     fn get_pre_vote_proposals() {
         let (ctx, mut ctr, _) = setup_ctr(BOND);
@@ -1491,6 +3414,63 @@ mod unit_tests {
         assert_eq!(pre_vote_proposals.len(), 2);
     }
 
+    #[test]
+    fn vote_storage() {
+        let long = |c: char| AccountId::new_unchecked(c.to_string().repeat(64));
+        let (mut ctx, mut ctr, id) = setup_ctr(BOND);
+        let mut prop = ctr.proposals.get(&id).unwrap();
+        let storage_start = env::storage_usage();
+        ctr.add_vote(id, long('a'), Vote::Approve, Some(long('b')), &mut prop);
+        let delegated = env::storage_usage() - storage_start;
+        assert_eq!(delegated, 659);
+        assert!(delegated < VOTE_STORAGE);
+        ctr.proposals.insert(&id, &prop);
+
+        // first vote of the account, with the max length account ID
+        ctx.predecessor_account_id = iah_registry();
+        ctx.attached_deposit = VOTE_DEPOSIT;
+        testing_env!(ctx.clone());
+        let storage_start = env::storage_usage();
+        assert_eq!(
+            ctr.vote(
+                long('c'),
+                min_vote_lock(&ctx),
+                None,
+                vote_payload(id, Vote::Approve)
+            ),
+            Ok(())
+        );
+        assert_eq!(env::storage_usage() - storage_start, 591);
+
+        // delegated Rank vote with a new ballot
+        let options = (0..10).map(|i| i.to_string()).collect();
+        ctx.attached_deposit = BOND;
+        testing_env!(ctx.clone());
+        let id = prop_id(ctr.create_proposal(
+            acc(1),
+            iah_proof(),
+            create_prop_payload(
+                PropKind::MultipleChoice {
+                    options,
+                    method: TallyMethod::InstantRunoff,
+                },
+                "drafts".to_string(),
+            ),
+        ));
+        let mut prop = ctr.proposals.get(&id).unwrap();
+        let storage_start = env::storage_usage();
+        ctr.add_vote(
+            id,
+            long('d'),
+            Vote::Rank(vec![9, 8, 7]),
+            Some(long('b')),
+            &mut prop,
+        );
+        let delegated = env::storage_usage() - storage_start;
+        assert_eq!(delegated, 782);
+        assert!(delegated < VOTE_STORAGE);
+    }
+
     #[test]
     fn vote_map() {
         let (ctx, mut ctr, id1) = setup_ctr(BOND);
@@ -1542,4 +3522,127 @@ mod unit_tests {
             "{\"Veto\":{\"dao\":\"hom.near\",\"prop_id\":12}}".to_string()
         );
     }
+
+    #[test]
+    fn migrate_proposals_and_votes() {
+        let mut ctx = VMContextBuilder::new().build();
+        ctx.predecessor_account_id = ctx.current_account_id.clone();
+        ctx.block_timestamp = START;
+        testing_env!(ctx);
+        let old_prop = |status| migrate::OldProposal {
+            proposer: acc(1),
+            bond: PRE_BOND,
+            additional_bond: None,
+            description: "old".to_string(),
+            kind: PropKind::Text,
+            status,
+            approve: 1,
+            reject: 0,
+            spam: 0,
+            abstain: 0,
+            support: 2,
+            supported: HashSet::from([acc(2)]),
+            start: START / MSECOND,
+            executed_at: None,
+            proposal_storage: 100,
+        };
+        let mut pre_vote = LookupMap::new(StorageKey::PreVoteProposals);
+        pre_vote.insert(&1, &old_prop(ProposalStatus::PreVote));
+        let mut proposals = LookupMap::new(StorageKey::Proposals);
+        // many proposals are migrated in batches
+        for id in 2..=25 {
+            proposals.insert(&id, &old_prop(ProposalStatus::InProgress));
+        }
+        // vote record before adding `delegate`
+        let mut old_votes: LookupMap<(u32, AccountId), (u64, Vote)> =
+            LookupMap::new(StorageKey::Votes);
+        old_votes.insert(&(2, acc(1)), &(10, Vote::Approve));
+        let mut legacy_whitelist = near_sdk::store::LookupSet::new(StorageKey::IomWhitelist);
+        legacy_whitelist.insert(acc(3));
+        legacy_whitelist.insert(acc(4));
+        let consent = |quorum| migrate::OldConsent {
+            quorum,
+            threshold: 50,
+        };
+        env::state_write(&migrate::OldState {
+            prop_counter: 25,
+            pre_vote_proposals: pre_vote,
+            proposals,
+            votes: LookupMap::new(StorageKey::Votes),
+            pre_vote_bond: PRE_BOND,
+            active_queue_bond: BOND,
+            pre_vote_support: PRE_VOTE_SUPPORT,
+            simple_consent: consent(3),
+            super_consent: consent(5),
+            pre_vote_duration: PRE_VOTE_DURATION,
+            vote_duration: VOTE_DURATION,
+            accounts: LazyOption::new(
+                StorageKey::Accounts,
+                Some(&Accounts {
+                    iah_registry: iah_registry(),
+                    community_treasury: treasury(),
+                    congress_hom: hom(),
+                    congress_coa: coa(),
+                    congress_tc: tc(),
+                    admin: admin(),
+                }),
+            ),
+            iom_whitelist: legacy_whitelist,
+        });
+
+        let mut ctr = Contract::migrate(vec![acc(3), acc(5)]);
+        assert_eq!(ctr.migration, Some((1, 25)));
+        assert!(!ctr.migrate_proposals(10));
+        assert_eq!(ctr.migration, Some((11, 25)));
+        assert!(ctr.get_proposal(10).is_some());
+        assert!(!ctr.migrate_proposals(10));
+        assert!(ctr.migrate_proposals(10));
+        assert_eq!(ctr.migration, None);
+        assert!(ctr.migrate_proposals(10));
+        for id in 1..=25 {
+            assert!(ctr.get_proposal(id).is_some());
+        }
+        assert!(ctr.get_proposal(26).is_none());
+
+        let p = ctr.get_proposal(1).unwrap().proposal;
+        assert_eq!(p.status, ProposalStatus::PreVote);
+        assert_eq!(p.supported, HashSet::from([acc(2)]));
+        let p = ctr.get_proposal(2).unwrap().proposal;
+        assert_eq!(p.status, ProposalStatus::InProgress);
+        assert_eq!((p.approve, p.quorum, p.voters), (1, None, 0));
+        assert_eq!(p.votes_hash_chain, vec![0; 32].into());
+        assert_eq!(p.ballots, 0);
+        assert_eq!(
+            ctr.get_vote(2, acc(1)),
+            Some(VoteRecord {
+                timestamp: 10,
+                vote: Vote::Approve,
+                delegate: None,
+            })
+        );
+        // only the listed legacy whitelist entries are migrated
+        let now = START / MSECOND;
+        assert_eq!(
+            ctr.get_whitelist(0, 10),
+            vec![WhitelistOutput {
+                account: acc(3),
+                entry: WhitelistEntry {
+                    expires_at: now + LEGACY_WHITELIST_DURATION,
+                    reason: env::sha256(LEGACY_WHITELIST_REASON).into(),
+                    authority: admin(),
+                    added_at: now,
+                },
+            }]
+        );
+        assert!(!ctr.is_iom_whitelisted(&acc(4)));
+        assert_eq!(ctr.get_roles(), admin_roles(&admin()));
+
+        // votes cast before the migration are not in the voters index, but still block withdraw
+        let mut ctx = VMContextBuilder::new().build();
+        ctx.predecessor_account_id = acc(1);
+        ctx.block_timestamp = START;
+        testing_env!(ctx);
+        assert_eq!(ctr.withdraw_proposal(2), Err(WithdrawError::AlreadyVoted));
+        assert!(ctr.get_proposal(2).is_some());
+    }
 }
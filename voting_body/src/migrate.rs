@@ -1,12 +1,80 @@
 use crate::*;
 
+/// Consent before adding `quorum_percent`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldConsent {
+    pub quorum: u32,
+    pub threshold: u8,
+}
+
+impl From<OldConsent> for Consent {
+    fn from(c: OldConsent) -> Self {
+        Consent {
+            quorum: c.quorum,
+            threshold: c.threshold,
+            quorum_percent: None,
+        }
+    }
+}
+
+/// Proposal before adding the quorum snapshot, votes hash chain and index, secret ballot and
+/// multiple choice ballots.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldProposal {
+    pub proposer: AccountId,
+    pub bond: Balance,
+    pub additional_bond: Option<(AccountId, Balance)>,
+    pub description: String,
+    pub kind: PropKind,
+    pub status: ProposalStatus,
+    pub approve: u32,
+    pub reject: u32,
+    pub spam: u32,
+    pub abstain: u32,
+    pub support: u32,
+    pub supported: HashSet<AccountId>,
+    pub start: u64,
+    pub executed_at: Option<u64>,
+    pub proposal_storage: u128,
+}
+
+impl From<OldProposal> for Proposal {
+    fn from(p: OldProposal) -> Self {
+        Proposal {
+            proposer: p.proposer,
+            bond: p.bond,
+            additional_bond: p.additional_bond,
+            description: p.description,
+            kind: p.kind,
+            status: p.status,
+            approve: p.approve,
+            reject: p.reject,
+            spam: p.spam,
+            abstain: p.abstain,
+            support: p.support,
+            supported: p.supported,
+            start: p.start,
+            executed_at: p.executed_at,
+            proposal_storage: p.proposal_storage,
+            // quorum of the proposal consent is used.
+            quorum: None,
+            // votes cast before the migration are not included in the hash chain and the index.
+            votes_hash_chain: vec![0; 32].into(),
+            voters: 0,
+            reveal_duration: None,
+            commits: 0,
+            ballots: 0,
+        }
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct OldState {
     pub prop_counter: u32,
     /// Set of proposals in the pre-vote queue.
-    pub pre_vote_proposals: LookupMap<u32, Proposal>,
+    pub pre_vote_proposals: LookupMap<u32, OldProposal>,
     /// Set of active proposals.
-    pub proposals: LookupMap<u32, Proposal>,
+    pub proposals: LookupMap<u32, OldProposal>,
     /// map (prop_id, voter) -> VoteRecord
     pub votes: LookupMap<(u32, AccountId), VoteRecord>,
 
@@ -19,35 +87,111 @@ pub struct OldState {
 
     /// minimum amount of members to approve the proposal
     /// u32 can hold a number up to 4.2 B. That is enough for many future iterations.
-    pub simple_consent: Consent,
-    pub super_consent: Consent,
+    pub simple_consent: OldConsent,
+    pub super_consent: OldConsent,
 
     /// all times below are in milliseconds
     pub pre_vote_duration: u64,
     pub vote_duration: u64,
     pub accounts: LazyOption<Accounts>,
+    pub iom_whitelist: near_sdk::store::LookupSet<AccountId>,
 }
 
 #[near_bindgen]
 impl Contract {
+    /// Migrates the contract state. The legacy IOM whitelist (without expiry) can't be
+    /// enumerated, so the whitelisted accounts must be provided in `whitelisted`. They are
+    /// converted to whitelist entries added by the admin, which expire after
+    /// `LEGACY_WHITELIST_DURATION`. Accounts not in the legacy whitelist are ignored. Legacy
+    /// entries which are not listed are no longer accepted.
+    /// The admin is granted all roles. Stored proposals are converted in batches with
+    /// `migrate_proposals`. Until then, the old proposals can't be read.
     #[private]
     #[init(ignore_state)]
-    pub fn migrate() -> Self {
+    pub fn migrate(whitelisted: Vec<AccountId>) -> Self {
         let old_state: OldState = env::state_read().expect("Old state doesn't exist");
+        let mut iom_whitelist = old_state.iom_whitelist;
+        let mut whitelist = UnorderedMap::new(StorageKey::Whitelist);
+        let admin = old_state.accounts.get().unwrap().admin;
+        let now = env::block_timestamp_ms();
+        let entry = WhitelistEntry {
+            expires_at: now + LEGACY_WHITELIST_DURATION,
+            reason: env::sha256(LEGACY_WHITELIST_REASON).into(),
+            authority: admin.clone(),
+            added_at: now,
+        };
+        for user in whitelisted {
+            if iom_whitelist.remove(&user) {
+                emit_whitelist_add(&user, &entry);
+                whitelist.insert(&user, &entry);
+            }
+        }
         Self {
             prop_counter: old_state.prop_counter,
-            pre_vote_proposals: old_state.pre_vote_proposals,
-            proposals: old_state.proposals,
+            pre_vote_proposals: LookupMap::new(StorageKey::PreVoteProposals),
+            proposals: LookupMap::new(StorageKey::Proposals),
             votes: old_state.votes,
             pre_vote_bond: old_state.pre_vote_bond,
             active_queue_bond: old_state.active_queue_bond,
             pre_vote_support: old_state.pre_vote_support,
-            simple_consent: old_state.simple_consent,
-            super_consent: old_state.super_consent,
+            simple_consent: old_state.simple_consent.into(),
+            super_consent: old_state.super_consent.into(),
             pre_vote_duration: old_state.pre_vote_duration,
             vote_duration: old_state.vote_duration,
             accounts: old_state.accounts,
-            iom_whitelist: LookupSet::new(StorageKey::IomWhitelist),
+            // new fields
+            withdraw_fee: WITHDRAW_FEE,
+            population: None,
+            delegations: LookupMap::new(StorageKey::Delegations),
+            delegators: LookupMap::new(StorageKey::Delegators),
+            delegates: LookupSet::new(StorageKey::Delegates),
+            ballots: LookupMap::new(StorageKey::Ballots),
+            ballot_index: LookupMap::new(StorageKey::BallotIndex),
+            voters: LookupMap::new(StorageKey::Voters),
+            account_votes: LookupMap::new(StorageKey::AccountVotes),
+            account_votes_len: LookupMap::new(StorageKey::AccountVotesLen),
+            vote_index: LookupMap::new(StorageKey::VoteIndex),
+            reveal_duration: REVEAL_DURATION,
+            commitments: LookupMap::new(StorageKey::Commitments),
+            constitutions: Vector::new(StorageKey::Constitutions),
+            roles: LazyOption::new(StorageKey::Roles, Some(&admin_roles(&admin))),
+            whitelist,
+            migration: if old_state.prop_counter > 0 {
+                Some((1, old_state.prop_counter))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Converts up to `limit` stored proposals (pre-vote and active) to the new layout,
+    /// continuing from the last call. Must be called until it returns true (all proposals are
+    /// migrated).
+    #[private]
+    pub fn migrate_proposals(&mut self, limit: u32) -> bool {
+        let (next, last) = match self.migration {
+            Some(m) => m,
+            None => return true,
+        };
+        // same storage prefixes. The old value is removed first, because `insert` deserializes
+        // the previous value.
+        let mut old_pre_vote: LookupMap<u32, OldProposal> =
+            LookupMap::new(StorageKey::PreVoteProposals);
+        let mut old_proposals: LookupMap<u32, OldProposal> = LookupMap::new(StorageKey::Proposals);
+        let end = last.min(next.saturating_add(limit).saturating_sub(1));
+        for id in next..=end {
+            if let Some(p) = old_pre_vote.remove(&id) {
+                self.pre_vote_proposals.insert(&id, &p.into());
+            }
+            if let Some(p) = old_proposals.remove(&id) {
+                self.proposals.insert(&id, &p.into());
+            }
         }
+        self.migration = if end < last {
+            Some((end + 1, last))
+        } else {
+            None
+        };
+        self.migration.is_none()
     }
 }
@@ -3,26 +3,51 @@ use near_sdk::json_types::{Base64VecU8, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, AccountId, Balance, Promise};
 
+use std::cmp::{max, min};
 use std::collections::HashSet;
 
-use crate::{PrevoteError, SLASH_REWARD};
+use crate::{PrevoteError, VoteError, MAX_RANKING, SLASH_REWARD};
 
 /// Consent sets the conditions for vote to pass. It specifies a quorum (minimum amount of
 /// accounts that have to vote and the approval threshold (% of #approve votes) for a proposal
 /// to pass.
-#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone, Copy)]
 #[serde(crate = "near_sdk::serde")]
-#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq, Copy))]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
 pub struct Consent {
+    /// Absolute quorum. When `quorum_percent` is set, it's the minimum quorum.
     pub quorum: u32,
     /// percentage value
     pub threshold: u8,
+    /// Quorum as a percentage of the IAH population (number of verified humans).
+    #[serde(default)]
+    pub quorum_percent: Option<u8>,
 }
 
 impl Consent {
     pub fn verify(&self) -> bool {
+        self.verify_threshold() && self.verify_quorum_percent()
+    }
+
+    pub fn verify_threshold(&self) -> bool {
         self.threshold <= 100
     }
+
+    pub fn verify_quorum_percent(&self) -> bool {
+        self.quorum_percent.unwrap_or(0) <= 100
+    }
+
+    /// Returns the quorum for the given IAH population: `quorum_percent` of the population
+    /// (rounded up), but not less than the absolute `quorum`.
+    pub fn quorum_for(&self, population: u64) -> u32 {
+        match self.quorum_percent {
+            None => self.quorum,
+            Some(percent) => {
+                let q = (population * percent as u64 + 99) / 100;
+                max(self.quorum, min(q, u32::MAX as u64) as u32)
+            }
+        }
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -41,7 +66,8 @@ pub enum ConsentKind {
 pub struct Proposal {
     /// Original proposer.
     pub proposer: AccountId,
-    /// original bond, used to cover the storage for all votes
+    /// original bond, used to cover the proposal storage. Vote storage (up to `VOTE_STORAGE`
+    /// per vote) is paid by the voters.
     pub bond: Balance,
     pub(crate) additional_bond: Option<(AccountId, Balance)>,
     /// Description of this proposal.
@@ -50,6 +76,7 @@ pub struct Proposal {
     pub kind: PropKind,
     /// Current status of the proposal.
     pub status: ProposalStatus,
+    /// Number of approvals. For a multiple choice proposal: number of `Rank` ballots.
     pub approve: u32,
     pub reject: u32,
     pub spam: u32,
@@ -63,6 +90,25 @@ pub struct Proposal {
     pub executed_at: Option<u64>,
     /// Proposal storage cost (excluding vote)
     pub(crate) proposal_storage: u128,
+    /// Quorum snapshot, taken when the proposal enters the active queue. `None` for
+    /// proposals in the pre-vote queue.
+    pub quorum: Option<u32>,
+    /// Hash chain of all votes recorded for the proposal (including vote changes and delegated
+    /// votes): `sha256(prev_hash || borsh(voter, vote))`, updated with every recorded vote.
+    /// Allows to verify the list of votes (`vote` events) once the vote records are removed
+    /// (see `reclaim_vote_storage`). NOTE: it's not a Merkle root, a single vote can't be
+    /// verified without all the votes recorded before it. Zeros until the first vote, so the
+    /// proposal size doesn't change when voting.
+    pub votes_hash_chain: Base64VecU8,
+    /// Number of accounts which voted for the proposal (length of the proposal voters index).
+    pub voters: u32,
+    /// Reveal window duration (in milliseconds) of a secret ballot proposal, set when the
+    /// proposal is created. `None` for a public ballot.
+    pub reveal_duration: Option<u64>,
+    /// Number of vote commitments which are not revealed yet (secret ballot).
+    pub commits: u32,
+    /// Number of distinct multiple choice ballots (length of the proposal ballots index).
+    pub ballots: u32,
 }
 
 impl Proposal {
@@ -75,25 +121,61 @@ impl Proposal {
         Ok(())
     }
 
+    pub fn remove_support(&mut self, user: &AccountId) -> Result<(), PrevoteError> {
+        if !self.supported.remove(user) {
+            return Err(PrevoteError::NotSupported);
+        }
+        self.support -= 1;
+        Ok(())
+    }
+
+    /// Appends a new vote to the `votes_hash_chain`.
+    pub fn add_to_hash_chain(&mut self, voter: &AccountId, vote: &Vote) {
+        let mut data = std::mem::take(&mut self.votes_hash_chain.0);
+        data.extend((voter, vote).try_to_vec().unwrap());
+        self.votes_hash_chain = env::sha256(&data).into();
+    }
+
+    /// Returns true if any vote was recorded or committed. Checks the tally as well, because
+    /// `voters` is not set for votes cast before the votes index was added.
+    pub fn has_votes(&self) -> bool {
+        self.voters > 0
+            || self.commits > 0
+            || self.approve + self.reject + self.spam + self.abstain > 0
+    }
+
+    /// Returns true if the voting (including the reveal window of a secret ballot) is not over.
     pub fn is_active(&self, vote_duration: u64) -> bool {
-        env::block_timestamp_ms() <= self.start + vote_duration
+        env::block_timestamp_ms() <= self.start + vote_duration + self.reveal_duration.unwrap_or(0)
     }
 
-    pub fn recompute_status(&mut self, vote_duration: u64, consent: Consent) {
+    /// `choice_result` is called to tally a multiple choice proposal once the voting is over.
+    pub fn recompute_status(
+        &mut self,
+        vote_duration: u64,
+        consent: Consent,
+        choice_result: impl FnOnce(&Self) -> Option<ChoiceResult>,
+    ) {
         // still in progress or already finalzied
         if self.is_active(vote_duration) || self.status != ProposalStatus::InProgress {
             return;
         }
+        // multiple choice ballots count as approvals, the proposal requires a winner.
+        let approve = self.approve;
         let total_no = self.reject + self.spam;
-        let qualified = self.approve + total_no;
+        let qualified = approve + total_no;
 
-        // check if we have quorum
-        if qualified + self.abstain < consent.quorum {
+        // check if we have quorum. Not revealed votes count as abstain.
+        if qualified + self.abstain + self.commits < self.quorum.unwrap_or(consent.quorum) {
             self.status = ProposalStatus::Rejected;
             return;
         }
 
-        if self.approve > qualified * consent.threshold as u32 / 100 {
+        let has_winner = match choice_result(self) {
+            Some(r) => r.winner.is_some(),
+            None => true,
+        };
+        if approve > qualified * consent.threshold as u32 / 100 && has_winner {
             self.status = ProposalStatus::Approved;
         } else if self.spam > self.reject
             && total_no >= qualified * (100 - consent.threshold) as u32 / 100
@@ -104,6 +186,82 @@ impl Proposal {
         }
     }
 
+    /// Checks if the vote is valid for the proposal kind: multiple choice proposals only accept
+    /// `Rank` (with at most `MAX_RANKING` options), `Spam` and `Abstain` votes, other proposals
+    /// don't accept `Rank`.
+    pub fn check_vote(&self, vote: &Vote) -> Result<(), VoteError> {
+        match (&self.kind, vote) {
+            (PropKind::MultipleChoice { options, method }, Vote::Rank(ranking)) => {
+                let mut seen = vec![false; options.len()];
+                if ranking.is_empty()
+                    || ranking.len() > MAX_RANKING
+                    || (matches!(method, TallyMethod::Plurality) && ranking.len() > 1)
+                {
+                    return Err(VoteError::BadVote);
+                }
+                for o in ranking {
+                    match seen.get_mut(*o as usize) {
+                        Some(s) if !*s => *s = true,
+                        _ => return Err(VoteError::BadVote),
+                    }
+                }
+                Ok(())
+            }
+            (PropKind::MultipleChoice { .. }, Vote::Approve | Vote::Reject) => {
+                Err(VoteError::BadVote)
+            }
+            (_, Vote::Rank(_)) => Err(VoteError::BadVote),
+            _ => Ok(()),
+        }
+    }
+
+    /// Tallies the multiple choice `ballots`: list of (ranking, number of votes). Returns `None`
+    /// if the proposal is not a multiple choice proposal.
+    pub fn choice_result(&self, ballots: &[(Vec<u8>, u32)]) -> Option<ChoiceResult> {
+        let (n, method) = match &self.kind {
+            PropKind::MultipleChoice { options, method } => (options.len(), method),
+            _ => return None,
+        };
+        let mut eliminated = vec![false; n];
+        let mut rounds = vec![];
+        loop {
+            let mut counts = vec![0u32; n];
+            for (ranking, votes) in ballots {
+                if let Some(o) = ranking.iter().find(|o| !eliminated[**o as usize]) {
+                    counts[*o as usize] += votes;
+                }
+            }
+            let total: u32 = counts.iter().sum();
+            let max = *counts.iter().max().unwrap_or(&0);
+            let leaders: Vec<usize> = (0..n).filter(|i| max > 0 && counts[*i] == max).collect();
+            rounds.push(counts.clone());
+
+            let winner = match method {
+                TallyMethod::Plurality if leaders.len() == 1 => Some(leaders[0] as u8),
+                TallyMethod::InstantRunoff if max * 2 > total => Some(leaders[0] as u8),
+                TallyMethod::InstantRunoff if total > 0 => {
+                    let min = (0..n)
+                        .filter(|i| !eliminated[*i])
+                        .map(|i| counts[i])
+                        .min()
+                        .unwrap_or(0);
+                    for i in 0..n {
+                        if counts[i] == min {
+                            eliminated[i] = true;
+                        }
+                    }
+                    if eliminated.iter().all(|e| *e) {
+                        None
+                    } else {
+                        continue;
+                    }
+                }
+                _ => None,
+            };
+            return Some(ChoiceResult { rounds, winner });
+        }
+    }
+
     /// Refund after voting period is over
     pub fn refund_bond(&mut self) -> bool {
         if self.bond == 0 {
@@ -122,6 +280,26 @@ impl Proposal {
     }
 
     /// returns false if there is nothing to slash.
+    /// Refunds the bond (including the storage deposit, the proposal must be removed) minus
+    /// the `fee`, which is sent to the `treasury`. Additional bond is fully refunded.
+    /// Returns the refunded amount and the charged fee.
+    pub fn withdraw_bond(&mut self, fee: Balance, treasury: AccountId) -> (Balance, Balance) {
+        let fee = min(fee, self.bond);
+        let refund = self.bond - fee;
+        if fee > 0 {
+            Promise::new(treasury).transfer(fee);
+        }
+        if refund > 0 {
+            Promise::new(self.proposer.clone()).transfer(refund);
+        }
+        if let Some((account, amount)) = &self.additional_bond {
+            Promise::new(account.clone()).transfer(*amount);
+        }
+        self.bond = 0;
+        self.additional_bond = None;
+        (refund, fee)
+    }
+
     pub fn slash_bond(&mut self, treasury: AccountId) -> bool {
         if self.bond == 0 {
             return false;
@@ -184,6 +362,45 @@ pub enum PropKind {
         pre_vote_duration: u64,
         vote_duration: u64,
     },
+    /// Selects one of the `options` (eg: constitution drafts). Voters cast `Vote::Rank`
+    /// ballots. The proposal is approved if there is a winner (see `ChoiceResult`).
+    /// No action is performed on execution.
+    MultipleChoice {
+        options: Vec<String>,
+        method: TallyMethod,
+    },
+    /// Ratifies a new version of the NDC constitution. Once executed, the constitution is
+    /// recorded in the contract (see `current_constitution`).
+    RatifyConstitution {
+        /// hash of the constitution document
+        document_hash: Base64VecU8,
+        /// URI of the constitution document
+        uri: String,
+        /// must be greater than the version of the current constitution
+        version: u32,
+    },
+    /// Updates the simple and super consents. Both must be valid (`Consent::verify`), with
+    /// threshold at least `MIN_THRESHOLD`, and the super consent can't be weaker than the
+    /// simple consent.
+    UpdateConsent {
+        simple_consent: Consent,
+        super_consent: Consent,
+    },
+    /// Updates the amount of supporters required to move a proposal to the active queue.
+    /// Must be between 1 and `MAX_PRE_VOTE_SUPPORT`.
+    UpdatePreVoteSupport {
+        pre_vote_support: u32,
+    },
+    /// Grants the `role` to the `account`.
+    GrantRole {
+        account: AccountId,
+        role: Role,
+    },
+    /// Revokes the `role` from the `account`.
+    RevokeRole {
+        account: AccountId,
+        role: Role,
+    },
 }
 
 impl PropKind {
@@ -199,6 +416,12 @@ impl PropKind {
             PropKind::FunctionCall { .. } => "function call".to_string(),
             PropKind::UpdateBonds { .. } => "config: update bonds".to_string(),
             PropKind::UpdateVoteDuration { .. } => "config: update voting duration".to_string(),
+            PropKind::MultipleChoice { .. } => "multiple choice".to_string(),
+            PropKind::RatifyConstitution { .. } => "ratify constitution".to_string(),
+            PropKind::UpdateConsent { .. } => "config: update consent".to_string(),
+            PropKind::UpdatePreVoteSupport { .. } => "config: update pre-vote support".to_string(),
+            PropKind::GrantRole { .. } => "grant role".to_string(),
+            PropKind::RevokeRole { .. } => "revoke role".to_string(),
         }
     }
 
@@ -210,8 +433,15 @@ impl PropKind {
             | Self::Text
             | Self::FunctionCall { .. }
             | Self::UpdateBonds { .. }
-            | Self::UpdateVoteDuration { .. } => ConsentKind::Simple,
-            Self::Dissolve { .. } | Self::TextSuper => ConsentKind::Super,
+            | Self::UpdateVoteDuration { .. }
+            | Self::MultipleChoice { .. }
+            | Self::RevokeRole { .. } => ConsentKind::Simple,
+            Self::Dissolve { .. }
+            | Self::TextSuper
+            | Self::UpdateConsent { .. }
+            | Self::UpdatePreVoteSupport { .. }
+            | Self::GrantRole { .. }
+            | Self::RatifyConstitution { .. } => ConsentKind::Super,
         }
     }
 }
@@ -233,26 +463,87 @@ pub enum ProposalStatus {
     Failed,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize)]
+#[derive(BorshSerialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
 pub struct VoteRecord {
     pub timestamp: u64, // unix time of when this vote was submitted
     pub vote: Vote,
+    /// Delegate who cast the vote on behalf of the voter. `None` for a direct vote.
+    pub delegate: Option<AccountId>,
+}
+
+/// Vote records are not migrated (they can't be enumerated): records stored before vote
+/// delegation don't have the `delegate` field, which is decoded as `None`.
+impl BorshDeserialize for VoteRecord {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let timestamp: u64 = BorshDeserialize::deserialize(buf)?;
+        let vote: Vote = BorshDeserialize::deserialize(buf)?;
+        let delegate: Option<AccountId> = if buf.is_empty() {
+            None
+        } else {
+            BorshDeserialize::deserialize(buf)?
+        };
+        Ok(VoteRecord {
+            timestamp,
+            vote,
+            delegate,
+        })
+    }
 }
 
 /// Votes recorded in the proposal.
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
-#[cfg_attr(not(target_arch = "wasm32"), derive(Clone, Debug, PartialEq))]
+/// NOTE: borsh encodes the variant index (`Approve=0`, `Reject=1`, `Spam=2`, `Abstain=3`,
+/// `Rank=4`).
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
 #[serde(crate = "near_sdk::serde")]
 pub enum Vote {
-    Approve = 0x0,
-    Reject = 0x1,
+    Approve,
+    Reject,
     /// Spam vote indicates that the proposal creates a spam, must be removed and the bond
     /// slashed.
-    Spam = 0x2,
-    Abstain = 0x3,
+    Spam,
+    Abstain,
     // note: we don't have Remove, we use Spam.
+    /// Ballot for a multiple choice proposal: list of option indexes, ordered by preference.
+    /// `Plurality` ballot must have exactly one option.
+    Rank(Vec<u8>),
+}
+
+/// Roles granted by the Voting Body (`GrantRole` / `RevokeRole` proposals).
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    /// Allows to update the consents (`admin_update_consent`).
+    ConsentUpdater,
+    /// Allows to add and remove accounts from the IOM whitelist.
+    WhitelistManager,
+}
+
+/// Tally method of a multiple choice proposal.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Clone, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub enum TallyMethod {
+    /// The option with the most votes wins. Ties have no winner.
+    Plurality,
+    /// Ranked ballots. The option with the fewest first preferences is eliminated (all tied
+    /// options are eliminated together) and its ballots are transferred to the next preference,
+    /// until an option has a majority of the continuing ballots.
+    InstantRunoff,
+}
+
+/// Result of a multiple choice proposal tally.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct ChoiceResult {
+    /// Number of votes of each option, for each tally round (one round for `Plurality`).
+    pub rounds: Vec<Vec<u32>>,
+    /// Index of the winning option.
+    pub winner: Option<u8>,
 }
 
 /// Function call arguments.
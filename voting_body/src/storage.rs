@@ -1,4 +1,5 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U128};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{AccountId, BorshStorageKey};
 
@@ -9,7 +10,22 @@ pub enum StorageKey {
     Proposals,
     Accounts,
     Votes,
+    /// legacy IOM whitelist, migrated to `Whitelist`. Kept to preserve the following keys.
+    #[allow(dead_code)]
     IomWhitelist,
+    Delegations,
+    Delegators,
+    Voters,
+    AccountVotes,
+    AccountVotesLen,
+    Commitments,
+    Constitutions,
+    Roles,
+    Whitelist,
+    VoteIndex,
+    Delegates,
+    Ballots,
+    BallotIndex,
 }
 
 /// External account required for the Voting Body.
@@ -25,6 +41,73 @@ pub struct Accounts {
     pub admin: AccountId,
 }
 
+impl Accounts {
+    /// Returns true if the `account` is one of the congress houses.
+    pub fn is_congress(&self, account: &AccountId) -> bool {
+        *account == self.congress_hom
+            || *account == self.congress_coa
+            || *account == self.congress_tc
+    }
+}
+
+/// Vote delegation to another human.
+#[derive(BorshSerialize, BorshDeserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq, Clone))]
+pub struct Delegation {
+    pub delegate: AccountId,
+    /// The delegator is locked for soul transfers in the IAH registry until this time (unix
+    /// time in milliseconds). Delegated votes are only counted for proposals which voting ends
+    /// before that time.
+    pub locked_until: u64,
+    /// Storage deposit of the delegator, which pays the storage of the votes recorded by the
+    /// delegate on behalf of the delegator. Returned when the delegation is removed.
+    pub deposit: U128,
+}
+
+/// IAH population: supply of the `class` SBTs issued by `issuer` in the IAH registry.
+/// Used to compute the quorum from `Consent::quorum_percent`.
+#[derive(BorshSerialize, BorshDeserialize, Deserialize, Serialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+pub struct Population {
+    pub issuer: AccountId,
+    pub class: u64,
+    /// number of humans, as reported by the registry in the latest update.
+    pub count: u64,
+    /// unix time (in milliseconds) of the latest update.
+    pub updated_at: u64,
+}
+
+/// IOM whitelist entry.
+#[derive(BorshSerialize, BorshDeserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq, Clone))]
+pub struct WhitelistEntry {
+    /// unix time (in milliseconds) when the entry expires.
+    pub expires_at: u64,
+    /// hash of the reason / evidence for whitelisting the account.
+    pub reason: Base64VecU8,
+    /// account which added the entry (admin or `WhitelistManager`).
+    pub authority: AccountId,
+    /// unix time (in milliseconds) when the entry was added.
+    pub added_at: u64,
+}
+
+/// Constitution ratified by a `RatifyConstitution` proposal.
+#[derive(BorshSerialize, BorshDeserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq, Clone))]
+pub struct Constitution {
+    pub document_hash: Base64VecU8,
+    pub uri: String,
+    pub version: u32,
+    /// ID of the proposal which ratified the constitution.
+    pub prop_id: u32,
+    /// unix time (in milliseconds) of the ratification.
+    pub ratified_at: u64,
+}
+
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod unit_tests2 {
     use near_sdk::IntoStorageKey;
@@ -1,4 +1,5 @@
 use crate::{PropKind, Vote};
+use near_sdk::json_types::Base64VecU8;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::AccountId;
 
@@ -16,6 +17,48 @@ pub struct VotePayload {
 pub struct CreatePropPayload {
     pub kind: PropKind,
     pub description: String,
+    /// If true, votes are committed as hashes during the voting and revealed in the reveal
+    /// window (see `commit_vote` and `reveal_vote`).
+    #[serde(default)]
+    pub secret_ballot: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Clone))]
+#[serde(crate = "near_sdk::serde")]
+pub struct CommitPayload {
+    pub prop_id: u32,
+    /// `sha256(borsh(voter, vote) || salt)`
+    pub commitment: Base64VecU8,
+}
+
+/// Congress proposal status. Only statuses required by the Voting Body are distinguished.
+#[derive(Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub enum CongressPropStatus {
+    Approved,
+    Executed,
+    #[serde(other)]
+    Other,
+}
+
+/// Subset of the congress `get_proposal` response.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CongressProposal {
+    pub id: u32,
+    pub status: CongressPropStatus,
+    /// Congress proposal kind, eg: `{"Budget": "1000"}`.
+    pub kind: serde_json::Value,
+}
+
+/// Subset of the congress `vetoable_proposals` response.
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VetoableProposal {
+    pub id: u32,
+    pub veto_deadline: u64,
 }
 
 pub type SBTs = Vec<(AccountId, Vec<u64>)>;
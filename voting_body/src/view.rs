@@ -19,6 +19,36 @@ pub struct ProposalOutput {
     pub proposal: Proposal,
 }
 
+/// This is format of output via JSON for a vote of a proposal.
+#[derive(Serialize)]
+#[cfg_attr(test, derive(PartialEq, Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct VoteOutput {
+    pub voter: AccountId,
+    #[serde(flatten)]
+    pub record: VoteRecord,
+}
+
+/// This is format of output via JSON for a whitelist entry.
+#[derive(Serialize)]
+#[cfg_attr(test, derive(PartialEq, Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct WhitelistOutput {
+    pub account: AccountId,
+    #[serde(flatten)]
+    pub entry: WhitelistEntry,
+}
+
+/// This is format of output via JSON for a vote of an account.
+#[derive(Serialize)]
+#[cfg_attr(test, derive(PartialEq, Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountVoteOutput {
+    pub prop_id: u32,
+    #[serde(flatten)]
+    pub record: VoteRecord,
+}
+
 /// This is format of output via JSON for the config.
 #[derive(Serialize)]
 #[cfg_attr(test, derive(PartialEq, Debug))]
@@ -33,6 +63,9 @@ pub struct ConfigOutput {
     pub vote_duration: u64,
     pub pre_vote_duration: u64,
     pub accounts: Accounts,
+    pub population: Option<Population>,
+    pub withdraw_fee: U128,
+    pub reveal_duration: u64,
 }
 
 #[near_bindgen]
@@ -82,7 +115,7 @@ impl Contract {
 
         iter.filter_map(|id| {
             proposals.get(&id).map(|mut proposal| {
-                proposal.recompute_status(self.vote_duration, self.prop_consent(&proposal));
+                self.recompute_status(id, &mut proposal);
                 ProposalOutput { id, proposal }
             })
         })
@@ -96,7 +129,7 @@ impl Contract {
             p = self.pre_vote_proposals.get(&id);
         }
         p.map(|mut proposal| {
-            proposal.recompute_status(self.vote_duration, self.prop_consent(&proposal));
+            self.recompute_status(id, &mut proposal);
             ProposalOutput { id, proposal }
         })
     }
@@ -104,10 +137,100 @@ impl Contract {
     /// * `id`: proposal id
     /// * `voter`: account address.
     /// Returns none if voter didn't vote for the given proposal id.
+    /// If the vote was cast by a delegate on behalf of the voter, then `delegate` is set.
+    /// Otherwise it's a direct vote.
     pub fn get_vote(&self, id: u32, voter: AccountId) -> Option<VoteRecord> {
         self.votes.get(&(id, voter))
     }
 
+    /// Returns votes of the proposal in paginated view, in the order of the first vote of each
+    /// voter. `limit` voters are checked starting from `from_index` (0-based). Removed vote
+    /// records (see `reclaim_vote_storage`) are skipped, hence less than `limit` votes can be
+    /// returned.
+    pub fn get_votes(&self, prop_id: u32, from_index: u32, limit: u32) -> Vec<VoteOutput> {
+        let mut end = from_index.saturating_add(limit);
+        if let Some(p) = self.proposals.get(&prop_id) {
+            end = min(end, p.voters);
+        }
+        (from_index..end)
+            .filter_map(|i| self.voters.get(&(prop_id, i)))
+            .filter_map(|voter| {
+                let record = self.votes.get(&(prop_id, voter.clone()))?;
+                Some(VoteOutput { voter, record })
+            })
+            .collect()
+    }
+
+    /// Returns votes of the `account` in paginated view, ordered by the vote time.
+    /// `limit` votes are checked starting from `from_index` (0-based). Removed vote records
+    /// (see `reclaim_vote_storage`) are skipped, hence less than `limit` votes can be returned.
+    pub fn votes_by_account(
+        &self,
+        account: AccountId,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<AccountVoteOutput> {
+        let len = self.account_votes_len.get(&account).unwrap_or(0);
+        (from_index..min(len, from_index.saturating_add(limit)))
+            .filter_map(|i| self.account_votes.get(&(account.clone(), i)))
+            .filter_map(|prop_id| {
+                let record = self.votes.get(&(prop_id, account.clone()))?;
+                Some(AccountVoteOutput { prop_id, record })
+            })
+            .collect()
+    }
+
+    /// Returns the current tally of a multiple choice proposal. Returns `None` if the proposal
+    /// doesn't exist or is not a multiple choice proposal.
+    pub fn get_choice_result(&self, prop_id: u32) -> Option<ChoiceResult> {
+        self.choice_result(prop_id, &self.proposals.get(&prop_id)?)
+    }
+
+    /// Returns the latest constitution ratified by a `RatifyConstitution` proposal.
+    pub fn current_constitution(&self) -> Option<Constitution> {
+        let len = self.constitutions.len();
+        if len == 0 {
+            return None;
+        }
+        self.constitutions.get(len - 1)
+    }
+
+    /// Returns ratified constitutions in paginated view, ordered by version (oldest first).
+    pub fn constitutions(&self, from_index: u32, limit: u32) -> Vec<Constitution> {
+        (from_index as u64..min(self.constitutions.len(), from_index as u64 + limit as u64))
+            .filter_map(|i| self.constitutions.get(i))
+            .collect()
+    }
+
+    /// Returns accounts with roles granted by the Voting Body.
+    pub fn get_roles(&self) -> HashMap<AccountId, Vec<Role>> {
+        self.roles.get().unwrap()
+    }
+
+    /// Returns true if the `account` has the `role`.
+    pub fn has_role(&self, account: &AccountId, role: Role) -> bool {
+        self.roles
+            .get()
+            .unwrap()
+            .get(account)
+            .map_or(false, |r| r.contains(&role))
+    }
+
+    /// Returns the `account` delegation.
+    pub fn get_delegation(&self, account: AccountId) -> Option<Delegation> {
+        self.delegations.get(&account)
+    }
+
+    /// Returns true if the `account` accepts new delegations.
+    pub fn accepts_delegations(&self, account: AccountId) -> bool {
+        self.delegates.contains(&account)
+    }
+
+    /// Returns accounts which delegated their votes to the `delegate`.
+    pub fn get_delegators(&self, delegate: AccountId) -> Vec<AccountId> {
+        self.delegators.get(&delegate).unwrap_or_default()
+    }
+
     pub fn number_of_proposals(&self) -> u32 {
         self.prop_counter
     }
@@ -118,11 +241,14 @@ impl Contract {
             pre_vote_bond: U128(self.pre_vote_bond),
             active_queue_bond: U128(self.active_queue_bond),
             pre_vote_support: self.pre_vote_support,
-            simple_consent: self.simple_consent.clone(),
-            super_consent: self.super_consent.clone(),
+            simple_consent: self.simple_consent,
+            super_consent: self.super_consent,
             pre_vote_duration: self.pre_vote_duration,
             vote_duration: self.vote_duration,
             accounts: self.accounts.get().unwrap(),
+            population: self.population.clone(),
+            withdraw_fee: U128(self.withdraw_fee),
+            reveal_duration: self.reveal_duration,
         }
     }
 
@@ -136,7 +262,24 @@ impl Contract {
         self._get_proposals(from_index, limit, reverse, true)
     }
 
+    /// Returns true if the account has a not expired whitelist entry.
     pub fn is_iom_whitelisted(&self, account_id: &AccountId) -> bool {
-        self.iom_whitelist.contains(&account_id)
+        self.whitelist
+            .get(account_id)
+            .map_or(false, |e| e.expires_at > env::block_timestamp_ms())
+    }
+
+    /// Returns whitelist entries (including expired ones) in paginated view.
+    pub fn get_whitelist(&self, from_index: u32, limit: u32) -> Vec<WhitelistOutput> {
+        let keys = self.whitelist.keys_as_vector();
+        let values = self.whitelist.values_as_vector();
+        (from_index as u64..min(keys.len(), from_index as u64 + limit as u64))
+            .filter_map(|i| {
+                Some(WhitelistOutput {
+                    account: keys.get(i)?,
+                    entry: values.get(i)?,
+                })
+            })
+            .collect()
     }
 }
@@ -117,10 +117,12 @@ async fn init(worker: &Worker<impl DevNetwork>) -> anyhow::Result<Suite> {
     let simple_conset = Consent {
         quorum: 2,
         threshold: 2,
+        quorum_percent: None,
     };
     let super_consent = Consent {
         quorum: 3,
         threshold: 2,
+        quorum_percent: None,
     };
 
     // init voting body
@@ -203,6 +205,7 @@ async fn veto() -> anyhow::Result<()> {
             prop_id: 1,
         },
         description: "veto".to_string(),
+        secret_ballot: false,
     };
 
     // create veto proposal
@@ -267,6 +270,7 @@ async fn dismiss() -> anyhow::Result<()> {
             member: near_sdk::AccountId::new_unchecked(setup.alice.id().to_string()),
         },
         description: "dismiss".to_string(),
+        secret_ballot: false,
     };
 
     // create dismiss proposal
@@ -323,6 +327,7 @@ async fn dissolve() -> anyhow::Result<()> {
             dao: near_sdk::AccountId::new_unchecked(setup.hom_contract.id().to_string()),
         },
         description: "dissolve".to_string(),
+        secret_ballot: false,
     };
 
     // create dismiss proposal